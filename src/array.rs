@@ -4,57 +4,135 @@ use crate::Float;
 
 // [[file:../vecfx.note::*core][core:1]]
 #[inline]
-fn array_sub_generic<F, const N: usize>(x: [F; N], y: [F; N]) -> [F; N]
+fn array_zip_with<F, const N: usize>(x: [F; N], y: [F; N], f: impl Fn(F, F) -> F) -> [F; N]
 where
-    F: Float + std::fmt::Debug,
-{
-    use std::convert::TryInto;
-    let d: Vec<_> = x.iter().zip(y.iter()).map(|(a, b)| *a - *b).collect();
-    d.try_into().unwrap()
-}
-
-#[inline]
-fn array_add_generic<F, const N: usize>(x: [F; N], y: [F; N]) -> [F; N]
-where
-    F: Float + std::fmt::Debug,
+    F: Float,
 {
-    use std::convert::TryInto;
-    let d: Vec<_> = x.iter().zip(y.iter()).map(|(a, b)| *a + *b).collect();
-    d.try_into().unwrap()
+    std::array::from_fn(|i| f(x[i], y[i]))
 }
 
-/// Simple math for array
+/// Simple math for array, without heap allocation
 pub trait ArrayMathExt<F: Float> {
+    /// Adds two arrays. Returns x + y.
     fn array_add(self, other: Self) -> Self;
+    /// Subtracts two arrays. Returns x - y.
     fn array_sub(self, other: Self) -> Self;
+    /// Scale one array with a value.
     fn array_scale(self, value: F) -> Self;
+    /// Elementwise product. Returns x * y.
+    fn array_mul(self, other: Self) -> Self;
+    /// Elementwise division. Returns x / y.
+    fn array_div(self, other: Self) -> Self;
+    /// Componentwise minimum of two arrays.
+    fn array_min(self, other: Self) -> Self;
+    /// Componentwise maximum of two arrays.
+    fn array_max(self, other: Self) -> Self;
+    /// Returns -x.
+    fn array_neg(self) -> Self;
+    /// Dot product of two arrays.
+    fn array_dot(self, other: Self) -> F;
+    /// Squared L2 norm.
+    fn array_norm_squared(self) -> F;
+    /// L2 norm.
+    fn array_norm(self) -> F;
+    /// Return a unit vector along self, or `None` for a zero vector or one
+    /// with non-finite components. Components are scaled by the largest one
+    /// first, so the norm does not overflow or underflow.
+    fn array_normalize(self) -> Option<Self>
+    where
+        Self: Sized;
+    /// Euclidean distance between two points.
+    fn array_distance(self, other: Self) -> F;
+    /// Linear interpolation: (1 - t) * x + t * y. Returns exactly x for
+    /// `t = 0` and y for `t = 1` when both are finite.
+    fn array_lerp(self, other: Self, t: F) -> Self;
 }
 
 impl<F, const N: usize> ArrayMathExt<F> for [F; N]
 where
     F: Float + std::fmt::Debug,
 {
-    /// Adds two arrays. Returns x + y.
     fn array_add(self, other: Self) -> Self {
-        array_add_generic(self, other)
+        array_zip_with(self, other, |a, b| a + b)
     }
 
-    /// Subtracts two arrays. Returns x - y.
     fn array_sub(self, other: Self) -> Self {
-        array_sub_generic(self, other)
+        array_zip_with(self, other, |a, b| a - b)
     }
 
-    /// Scale one array with a value.
     fn array_scale(self, value: F) -> Self {
         self.map(|x| x * value)
     }
+
+    fn array_mul(self, other: Self) -> Self {
+        array_zip_with(self, other, |a, b| a * b)
+    }
+
+    fn array_div(self, other: Self) -> Self {
+        array_zip_with(self, other, |a, b| a / b)
+    }
+
+    fn array_min(self, other: Self) -> Self {
+        array_zip_with(self, other, F::min)
+    }
+
+    fn array_max(self, other: Self) -> Self {
+        array_zip_with(self, other, F::max)
+    }
+
+    fn array_neg(self) -> Self {
+        self.map(|x| -x)
+    }
+
+    fn array_dot(self, other: Self) -> F {
+        self.iter().zip(&other).fold(F::zero(), |s, (&a, &b)| s + a * b)
+    }
+
+    fn array_norm_squared(self) -> F {
+        self.array_dot(self)
+    }
+
+    fn array_norm(self) -> F {
+        self.array_norm_squared().sqrt()
+    }
+
+    fn array_normalize(self) -> Option<Self> {
+        let amax = self.iter().fold(F::zero(), |m, x| m.max(x.abs()));
+        if amax.is_zero() || !amax.is_finite() || self.iter().any(|x| x.is_nan()) {
+            return None;
+        }
+        let scaled = self.array_scale(amax.recip());
+        Some(scaled.array_scale(scaled.array_norm().recip()))
+    }
+
+    fn array_distance(self, other: Self) -> F {
+        self.array_sub(other).array_norm()
+    }
+
+    fn array_lerp(self, other: Self, t: F) -> Self {
+        array_zip_with(self, other, |a, b| (F::one() - t) * a + t * b)
+    }
+}
+
+/// Math specific to 3D arrays
+pub trait Array3MathExt<F: Float> {
+    /// Cross product. Returns x × y.
+    fn array_cross(self, other: Self) -> Self;
+}
+
+impl<F: Float> Array3MathExt<F> for [F; 3] {
+    fn array_cross(self, other: Self) -> Self {
+        let [x1, y1, z1] = self;
+        let [x2, y2, z2] = other;
+        [y1 * z2 - z1 * y2, z1 * x2 - x1 * z2, x1 * y2 - y1 * x2]
+    }
 }
 // core:1 ends here
 
 // [[file:../vecfx.note::*test][test:1]]
 #[test]
 fn test_array_sub_add() {
-    use crate::approx::*;
+    use approx::*;
 
     let p1 = [1.0, 2.0, 3.0];
     let p2 = [1.1, 2.2, 3.3];
@@ -72,3 +150,38 @@ fn test_array_sub_add() {
     assert_eq!(p5[2], 6.0);
 }
 // test:1 ends here
+
+// [[file:../vecfx.note::3c7e1a95][3c7e1a95]]
+#[test]
+fn test_array_3d_math() {
+    use approx::*;
+
+    let x = [1.0, 0.0, 0.0];
+    let y = [0.0, 2.0, 0.0];
+    assert_eq!(x.array_dot(y), 0.0);
+    assert_eq!(x.array_cross(y), [0.0, 0.0, 2.0]);
+    assert_eq!(y.array_cross(x), [0.0, 0.0, -2.0]);
+    assert_eq!(y.array_norm_squared(), 4.0);
+    assert_eq!(y.array_norm(), 2.0);
+    assert_eq!(y.array_normalize(), Some([0.0, 1.0, 0.0]));
+    assert_eq!([0.0; 3].array_normalize(), None);
+    assert_eq!([1e200, 0.0, 0.0].array_normalize(), Some([1.0, 0.0, 0.0]));
+    assert_eq!([0.0, -1e-300, 0.0].array_normalize(), Some([0.0, -1.0, 0.0]));
+    assert_eq!([f64::INFINITY, 0.0, 0.0].array_normalize(), None);
+    assert_relative_eq!(x.array_distance(y), 5f64.sqrt());
+    assert_eq!(x.array_lerp(y, 0.5), [0.5, 1.0, 0.0]);
+    assert_eq!(x.array_lerp(y, 1.0), y);
+    assert_eq!([-7.3].array_lerp([0.1], 1.0), [0.1]);
+
+    let p = [1.0, -2.0, 4.0];
+    let q = [2.0, 4.0, -8.0];
+    assert_eq!(p.array_mul(q), [2.0, -8.0, -32.0]);
+    assert_eq!(p.array_div(q), [0.5, -0.5, -0.5]);
+    assert_eq!(p.array_min(q), [1.0, -2.0, -8.0]);
+    assert_eq!(p.array_max(q), [2.0, 4.0, 4.0]);
+    assert_eq!(p.array_neg(), [-1.0, 2.0, -4.0]);
+
+    // works for f32 and other dimensions too
+    assert_eq!([3.0f32, 4.0].array_norm(), 5.0);
+}
+// 3c7e1a95 ends here
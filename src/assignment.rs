@@ -0,0 +1,132 @@
+// [[file:../vecfx.note::8d2f5a3b][8d2f5a3b]]
+/// Solve the linear assignment problem for a square cost matrix with the
+/// Hungarian algorithm in O(n^3). Returns for each row the column assigned
+/// to it, such that the total cost is minimal.
+///
+/// `cost` is given row by row as an n x n row-major slice. Returns `None` if
+/// any cost is infinite or NaN.
+///
+/// # Panics
+///
+/// * if `cost` does not have `n * n` elements.
+///
+/// # Example
+/// ```
+/// use vecfx::*;
+///
+/// #[rustfmt::skip]
+/// let cost = [
+///     4.0, 1.0, 3.0,
+///     2.0, 0.0, 5.0,
+///     3.0, 2.0, 2.0,
+/// ];
+/// assert_eq!(linear_assignment(&cost, 3), Some(vec![1, 0, 2]));
+/// ```
+pub fn linear_assignment(cost: &[f64], n: usize) -> Option<Vec<usize>> {
+    assert_eq!(cost.len(), n * n, "cost is not a {n}x{n} matrix");
+    // with non-finite costs no column may ever be found below `delta`
+    if cost.iter().any(|c| !c.is_finite()) {
+        return None;
+    }
+
+    // potentials u (rows) and v (columns), and the row matched to each
+    // column, all 1-based with 0 as a virtual column
+    let mut u = vec![0.0; n + 1];
+    let mut v = vec![0.0; n + 1];
+    let mut matched = vec![0; n + 1];
+    let mut way = vec![0; n + 1];
+    for i in 1..=n {
+        matched[0] = i;
+        let mut j0 = 0;
+        let mut minv = vec![f64::INFINITY; n + 1];
+        let mut used = vec![false; n + 1];
+        // find an augmenting path for row i
+        loop {
+            used[j0] = true;
+            let i0 = matched[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = 0;
+            for j in 1..=n {
+                if !used[j] {
+                    let cur = cost[(i0 - 1) * n + j - 1] - u[i0] - v[j];
+                    if cur < minv[j] {
+                        minv[j] = cur;
+                        way[j] = j0;
+                    }
+                    if minv[j] < delta {
+                        delta = minv[j];
+                        j1 = j;
+                    }
+                }
+            }
+            if j1 == 0 {
+                // no augmenting column, which finite costs rule out
+                return None;
+            }
+            for j in 0..=n {
+                if used[j] {
+                    u[matched[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    minv[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if matched[j0] == 0 {
+                break;
+            }
+        }
+        // augment along the path
+        while j0 != 0 {
+            let j1 = way[j0];
+            matched[j0] = matched[j1];
+            j0 = j1;
+        }
+    }
+
+    let mut assignment = vec![0; n];
+    for j in 1..=n {
+        assignment[matched[j] - 1] = j - 1;
+    }
+    Some(assignment)
+}
+// 8d2f5a3b ends here
+
+// [[file:../vecfx.note::35c0e9f6][35c0e9f6]]
+#[test]
+fn test_linear_assignment() {
+    // brute force over all permutations of 5
+    fn permutations(n: usize) -> Vec<Vec<usize>> {
+        if n == 0 {
+            return vec![vec![]];
+        }
+        let mut all = vec![];
+        for p in permutations(n - 1) {
+            for k in 0..n {
+                let mut q = p.clone();
+                q.insert(k, n - 1);
+                all.push(q);
+            }
+        }
+        all
+    }
+
+    let n = 5;
+    let cost: Vec<f64> = (0..n * n)
+        .map(|k| ((k * 37 + 11) % 17) as f64 - 0.5 * (k % 3) as f64)
+        .collect();
+    let total = |p: &[usize]| p.iter().enumerate().map(|(i, &j)| cost[i * n + j]).sum::<f64>();
+    let best = permutations(n).iter().map(|p| total(p)).fold(f64::INFINITY, f64::min);
+    let assignment = linear_assignment(&cost, n).unwrap();
+    assert_eq!(total(&assignment), best);
+
+    assert_eq!(linear_assignment(&[], 0), Some(vec![]));
+
+    // non-finite costs are rejected instead of looping forever
+    let mut bad = cost.clone();
+    bad[7] = f64::NAN;
+    assert_eq!(linear_assignment(&bad, n), None);
+    bad[7] = f64::INFINITY;
+    assert_eq!(linear_assignment(&bad, n), None);
+}
+// 35c0e9f6 ends here
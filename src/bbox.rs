@@ -0,0 +1,189 @@
+// [[file:../vecfx.note::0e4b7d62][0e4b7d62]]
+use crate::{gyration_tensor, symmetric_eigen3, ArrayMathExt, Mat3, Mat3Ext, VecFloatNDExt};
+// 0e4b7d62 ends here
+
+// [[file:../vecfx.note::93f2a6c1][93f2a6c1]]
+/// An axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    /// The corner with the smallest coordinates.
+    pub min: [f64; 3],
+    /// The corner with the largest coordinates.
+    pub max: [f64; 3],
+}
+
+impl Aabb {
+    /// Construct from two corners, in any order.
+    pub fn new(a: [f64; 3], b: [f64; 3]) -> Self {
+        Self {
+            min: a.array_min(b),
+            max: a.array_max(b),
+        }
+    }
+
+    /// Return the smallest box enclosing all `points`, or `None` if there is
+    /// no point.
+    pub fn from_points(points: &[[f64; 3]]) -> Option<Self> {
+        let (first, rest) = points.split_first()?;
+        let bbox = rest.iter().fold(Self::new(*first, *first), |b, p| Self {
+            min: b.min.array_min(*p),
+            max: b.max.array_max(*p),
+        });
+        Some(bbox)
+    }
+
+    /// The edge lengths along x, y and z.
+    pub fn extent(&self) -> [f64; 3] {
+        self.max.array_sub(self.min)
+    }
+
+    pub fn center(&self) -> [f64; 3] {
+        self.min.array_lerp(self.max, 0.5)
+    }
+
+    pub fn volume(&self) -> f64 {
+        self.extent().iter().product()
+    }
+
+    /// Return the box enlarged by `padding` on every side. A negative
+    /// `padding` shrinks it.
+    pub fn padded(&self, padding: f64) -> Self {
+        Self {
+            min: self.min.map(|x| x - padding),
+            max: self.max.map(|x| x + padding),
+        }
+    }
+
+    /// Return true if `point` lies inside or on the boundary.
+    pub fn contains(&self, point: [f64; 3]) -> bool {
+        (0..3).all(|i| self.min[i] <= point[i] && point[i] <= self.max[i])
+    }
+
+    /// Return true if `other` lies completely inside self.
+    pub fn contains_box(&self, other: &Self) -> bool {
+        self.contains(other.min) && self.contains(other.max)
+    }
+
+    /// Return true if self and `other` overlap or touch.
+    pub fn intersects(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Return the overlapping region, or `None` if the boxes are disjoint.
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = self.min.array_max(other.min);
+        let max = self.max.array_min(other.max);
+        (0..3).all(|i| min[i] <= max[i]).then_some(Self { min, max })
+    }
+
+    /// Return the smallest box enclosing both boxes.
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.array_min(other.min),
+            max: self.max.array_max(other.max),
+        }
+    }
+}
+// 93f2a6c1 ends here
+
+// [[file:../vecfx.note::6b1d8e47][6b1d8e47]]
+/// A bounding box aligned with the principal axes of a point set (PCA).
+/// Usually tighter than [`Aabb`] for elongated molecules, though not
+/// necessarily the smallest possible box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrientedBox {
+    pub center: [f64; 3],
+    /// Box axes stored as rows, ordered by increasing variance of the
+    /// points along them.
+    pub axes: Mat3,
+    /// Half of the edge lengths along each axis.
+    pub half_extents: [f64; 3],
+}
+
+impl OrientedBox {
+    /// Return the box along the principal axes of the covariance of
+    /// `points`, or `None` if there is no point.
+    pub fn from_points(points: &[[f64; 3]]) -> Option<Self> {
+        if points.is_empty() {
+            return None;
+        }
+        let centroid = points.centroid();
+        let axes = symmetric_eigen3(gyration_tensor(points, None)).eigenvectors;
+        let mut local = points.to_vec();
+        local.translate(centroid.array_neg());
+        axes.apply_to(&mut local);
+        let local = Aabb::from_points(&local)?;
+        let center = axes.transpose().matvec(local.center()).array_add(centroid);
+        Some(Self {
+            center,
+            axes,
+            half_extents: local.extent().array_scale(0.5),
+        })
+    }
+
+    /// The edge lengths along each axis.
+    pub fn extent(&self) -> [f64; 3] {
+        self.half_extents.array_scale(2.0)
+    }
+
+    pub fn volume(&self) -> f64 {
+        self.extent().iter().product()
+    }
+
+    /// Return true if `point` lies inside or on the boundary, within a
+    /// small tolerance for round-off.
+    pub fn contains(&self, point: [f64; 3]) -> bool {
+        let local = self.axes.matvec(point.array_sub(self.center));
+        let tol = 1e-12 * self.half_extents.iter().fold(1.0, |m: f64, &h| m.max(h));
+        (0..3).all(|i| local[i].abs() <= self.half_extents[i] + tol)
+    }
+
+    /// Return the eight corners of the box.
+    pub fn corners(&self) -> [[f64; 3]; 8] {
+        std::array::from_fn(|k| {
+            (0..3).fold(self.center, |c, i| {
+                let s = if k >> i & 1 == 0 { -1.0 } else { 1.0 };
+                c.array_add(self.axes[i].array_scale(s * self.half_extents[i]))
+            })
+        })
+    }
+}
+// 6b1d8e47 ends here
+
+// [[file:../vecfx.note::d2c7f950][d2c7f950]]
+#[test]
+fn test_bounding_boxes() {
+    use approx::*;
+
+    let points = [[0.0, 1.0, -1.0], [2.0, -1.0, 0.0], [1.0, 3.0, 1.0]];
+    let bbox = Aabb::from_points(&points).unwrap();
+    assert_eq!(bbox.min, [0.0, -1.0, -1.0]);
+    assert_eq!(bbox.max, [2.0, 3.0, 1.0]);
+    assert_eq!(bbox.extent(), [2.0, 4.0, 2.0]);
+    assert_eq!(bbox.center(), [1.0, 1.0, 0.0]);
+    assert_eq!(bbox.volume(), 16.0);
+    assert!(points.iter().all(|&p| bbox.contains(p)));
+    assert!(!bbox.contains([2.1, 0.0, 0.0]));
+    assert_eq!(Aabb::from_points(&[]), None);
+
+    let padded = bbox.padded(1.0);
+    assert_eq!(padded.extent(), [4.0, 6.0, 4.0]);
+    assert!(padded.contains_box(&bbox));
+    assert!(!bbox.contains_box(&padded));
+
+    let other = Aabb::new([3.0, 3.0, 3.0], [1.0, 2.0, 0.5]);
+    assert_eq!(bbox.intersection(&other), Some(Aabb::new([1.0, 2.0, 0.5], [2.0, 3.0, 1.0])));
+    assert!(!bbox.intersects(&Aabb::new([2.5; 3], [3.0; 3])));
+    assert_eq!(bbox.union(&other).max, [3.0, 3.0, 3.0]);
+
+    // a rod along the diagonal is much tighter in its own frame
+    let rod: Vec<_> = (0..=10).map(|i| [i as f64, i as f64, 0.1 * (i % 2) as f64]).collect();
+    let obb = OrientedBox::from_points(&rod).unwrap();
+    assert!(obb.volume() < 0.01 * Aabb::from_points(&rod).unwrap().volume());
+    assert_relative_eq!(obb.extent()[2], 200f64.sqrt(), epsilon = 1e-10);
+    assert!(rod.iter().all(|&p| obb.contains(p)));
+    assert!(!obb.contains([10.0, 0.0, 0.0]));
+    let corners = obb.corners();
+    assert_relative_eq!(corners[0].array_distance(corners[7]), obb.extent().array_norm(), epsilon = 1e-10);
+}
+// d2c7f950 ends here
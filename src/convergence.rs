@@ -0,0 +1,213 @@
+// [[file:../vecfx.note::b7e30a95][b7e30a95]]
+/// Thresholds for judging the convergence of a geometry optimization.
+///
+/// All values are in atomic units (Hartree, Bohr). A criterion set to `None`
+/// is not checked.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceCriteria {
+    /// Largest absolute component of the forces.
+    pub max_force: Option<f64>,
+    /// Root mean square of the forces.
+    pub rms_force: Option<f64>,
+    /// Largest absolute component of the displacement.
+    pub max_displacement: Option<f64>,
+    /// Root mean square of the displacement.
+    pub rms_displacement: Option<f64>,
+    /// Absolute energy change between two consecutive steps.
+    pub energy_change: Option<f64>,
+}
+
+impl Default for ConvergenceCriteria {
+    fn default() -> Self {
+        Self::gaussian()
+    }
+}
+
+impl ConvergenceCriteria {
+    /// The default criteria of Gaussian (`Opt`).
+    pub fn gaussian() -> Self {
+        Self {
+            max_force: Some(4.5e-4),
+            rms_force: Some(3.0e-4),
+            max_displacement: Some(1.8e-3),
+            rms_displacement: Some(1.2e-3),
+            energy_change: None,
+        }
+    }
+
+    /// The criteria of Gaussian `Opt=Tight`.
+    pub fn gaussian_tight() -> Self {
+        Self {
+            max_force: Some(1.5e-5),
+            rms_force: Some(1.0e-5),
+            max_displacement: Some(6.0e-5),
+            rms_displacement: Some(4.0e-5),
+            energy_change: None,
+        }
+    }
+
+    /// The criteria of Gaussian `Opt=VeryTight`.
+    pub fn gaussian_very_tight() -> Self {
+        Self {
+            max_force: Some(2.0e-6),
+            rms_force: Some(1.0e-6),
+            max_displacement: Some(6.0e-6),
+            rms_displacement: Some(4.0e-6),
+            energy_change: None,
+        }
+    }
+
+    /// The default criteria of ORCA (`NormalOpt`).
+    pub fn orca() -> Self {
+        Self {
+            max_force: Some(3.0e-4),
+            rms_force: Some(1.0e-4),
+            max_displacement: Some(4.0e-3),
+            rms_displacement: Some(2.0e-3),
+            energy_change: Some(5.0e-6),
+        }
+    }
+
+    /// The criteria of ORCA `TightOpt`.
+    pub fn orca_tight() -> Self {
+        Self {
+            max_force: Some(1.0e-4),
+            rms_force: Some(3.0e-5),
+            max_displacement: Some(1.0e-3),
+            rms_displacement: Some(6.0e-4),
+            energy_change: Some(1.0e-6),
+        }
+    }
+
+    /// Check the convergence of one optimization step.
+    ///
+    /// # Parameters
+    ///
+    /// * forces: the forces (or gradients) as a flat slice. Use
+    ///   `VecFloatNDExt::as_flat` for `[[f64; 3]]`.
+    /// * displacement: the step taken from the previous geometry, if any.
+    /// * energy_change: the energy difference from the previous step, if any.
+    ///
+    /// A criterion whose data is missing is reported as not converged.
+    ///
+    /// # Example
+    /// ```
+    /// use vecfx::*;
+    ///
+    /// let forces = [[1e-5, 0.0, -2e-5], [0.0, 3e-6, 0.0]];
+    /// let disp = [[1e-4, 0.0, 0.0], [0.0, -2e-4, 0.0]];
+    /// let report = ConvergenceCriteria::gaussian().check(forces.as_flat(), Some(disp.as_flat()), None);
+    /// assert!(report.converged());
+    /// assert!(report.to_string().contains("Max Force"));
+    /// ```
+    pub fn check(&self, forces: &[f64], displacement: Option<&[f64]>, energy_change: Option<f64>) -> ConvergenceReport {
+        let mut items = vec![];
+        let mut push = |name, value: Option<f64>, threshold: Option<f64>| {
+            if let Some(threshold) = threshold {
+                let value = value.unwrap_or(f64::NAN);
+                items.push(Criterion {
+                    name,
+                    value,
+                    threshold,
+                    converged: value.abs() <= threshold,
+                });
+            }
+        };
+
+        push("Max Force", Some(max_abs(forces)), self.max_force);
+        push("RMS Force", Some(rms(forces)), self.rms_force);
+        push("Max Displacement", displacement.map(max_abs), self.max_displacement);
+        push("RMS Displacement", displacement.map(rms), self.rms_displacement);
+        push("Energy Change", energy_change, self.energy_change);
+
+        ConvergenceReport { items }
+    }
+}
+
+/// The largest absolute value, or NaN if any value is NaN, so that a
+/// diverged step can never pass.
+fn max_abs(values: &[f64]) -> f64 {
+    values
+        .iter()
+        .map(|x| x.abs())
+        .fold(0.0, |m, x| if x.is_nan() || x > m { x } else { m })
+}
+
+fn rms(values: &[f64]) -> f64 {
+    if values.is_empty() {
+        0.0
+    } else {
+        (values.iter().map(|x| x * x).sum::<f64>() / values.len() as f64).sqrt()
+    }
+}
+// b7e30a95 ends here
+
+// [[file:../vecfx.note::46ca0e1b][46ca0e1b]]
+/// The outcome of a single convergence criterion.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Criterion {
+    pub name: &'static str,
+    pub value: f64,
+    pub threshold: f64,
+    pub converged: bool,
+}
+
+/// A per-criterion pass/fail table returned by [`ConvergenceCriteria::check`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvergenceReport {
+    pub items: Vec<Criterion>,
+}
+
+impl ConvergenceReport {
+    /// Return true if all criteria are satisfied.
+    pub fn converged(&self) -> bool {
+        self.items.iter().all(|x| x.converged)
+    }
+}
+
+impl std::fmt::Display for ConvergenceReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:<20}{:>14}{:>14}  Converged?", "Item", "Value", "Threshold")?;
+        for x in &self.items {
+            let flag = if x.converged { "YES" } else { "NO" };
+            writeln!(f, "{:<20}{:>14.6e}{:>14.6e}  {}", x.name, x.value, x.threshold, flag)?;
+        }
+        Ok(())
+    }
+}
+// 46ca0e1b ends here
+
+// [[file:../vecfx.note::d5a19e37][d5a19e37]]
+#[test]
+fn test_convergence_criteria() {
+    use crate::VecFloatNDExt;
+    use approx::*;
+
+    let forces = [[3.0e-4, -4.0e-4, 0.0], [0.0, 1.0e-4, -2.0e-4]];
+    let disp = [[1.0e-3, 0.0, 0.0], [0.0, 0.0, -2.5e-3]];
+
+    let report = ConvergenceCriteria::gaussian().check(forces.as_flat(), Some(disp.as_flat()), None);
+    assert_eq!(report.items.len(), 4);
+    assert_relative_eq!(report.items[0].value, 4.0e-4);
+    assert_relative_eq!(report.items[1].value, (3.0e-7f64 / 6.0).sqrt(), epsilon = 1e-12);
+    assert!(report.items[0].converged);
+    assert!(report.items[1].converged);
+    assert!(!report.items[2].converged);
+    assert!(!report.converged());
+
+    // missing energy change never converges
+    let report = ConvergenceCriteria::orca().check(forces.as_flat(), Some(disp.as_flat()), None);
+    assert_eq!(report.items.len(), 5);
+    assert!(!report.items[4].converged);
+    let report = ConvergenceCriteria::orca().check(forces.as_flat(), Some(disp.as_flat()), Some(-1e-6));
+    assert!(report.items[4].converged);
+    assert!(!report.items[0].converged);
+
+    // NaN forces or displacements never converge
+    let mut forces = [[1e-6; 3]; 3];
+    forces[1][2] = f64::NAN;
+    let disp = [1e-6, f64::NAN, 1e-6];
+    let report = ConvergenceCriteria::gaussian().check(forces.as_flat(), Some(&disp), None);
+    assert!(report.items.iter().all(|x| !x.converged));
+}
+// d5a19e37 ends here
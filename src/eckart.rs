@@ -0,0 +1,142 @@
+// [[file:../vecfx.note::e8a5d1c7][e8a5d1c7]]
+use crate::{Subspace, VecFloatExt, VecFloatNDExt};
+
+#[cfg(feature = "nalgebra")]
+use nalgebra as na;
+// e8a5d1c7 ends here
+
+// [[file:../vecfx.note::0f93b6e4][0f93b6e4]]
+/// Rotation vectors shorter than this fraction of the longest one are
+/// treated as vanishing, as for the rotation about the axis of a linear
+/// molecule.
+const LINEAR_TOL: f64 = 1e-6;
+
+/// Return the three translation and three rotation vectors (each of length
+/// 3N) of the rigid-body motions of `positions`, without orthonormalization.
+///
+/// With `masses`, the vectors are for mass-weighted coordinates: each atom
+/// is weighted by sqrt(m) and rotations are about the center of mass.
+/// Without, they are for plain Cartesian coordinates with rotations about
+/// the centroid.
+///
+/// # Panics
+///
+/// * if `masses` and `positions` differ in length.
+pub fn rigid_body_vectors(positions: &[[f64; 3]], masses: Option<&[f64]>) -> Vec<Vec<f64>> {
+    let n = positions.len();
+    let weights: Vec<f64> = match masses {
+        Some(m) => {
+            assert_eq!(m.len(), n, "masses and positions differ in size");
+            m.to_vec()
+        }
+        None => vec![1.0; n],
+    };
+
+    let center = positions.center_of_mass(&weights);
+
+    let mut vectors = vec![vec![0.0; 3 * n]; 6];
+    for (i, (p, w)) in positions.iter().zip(&weights).enumerate() {
+        let s = w.sqrt();
+        let [x, y, z] = [p[0] - center[0], p[1] - center[1], p[2] - center[2]];
+        for a in 0..3 {
+            vectors[a][3 * i + a] = s;
+        }
+        // e_a x r
+        vectors[3][3 * i..3 * i + 3].copy_from_slice(&[0.0, -s * z, s * y]);
+        vectors[4][3 * i..3 * i + 3].copy_from_slice(&[s * z, 0.0, -s * x]);
+        vectors[5][3 * i..3 * i + 3].copy_from_slice(&[-s * y, s * x, 0.0]);
+    }
+    vectors
+}
+
+/// Return the subspace of rigid-body translations and rotations of
+/// `positions`. Its dimension is 6 in general, 5 for linear molecules and
+/// 3 for a single atom. See [`rigid_body_vectors`] for `masses`.
+pub fn rigid_body_subspace(positions: &[[f64; 3]], masses: Option<&[f64]>) -> Subspace {
+    let mut vectors = rigid_body_vectors(positions, masses);
+    let rmax = vectors[3..].iter().map(|v| v.vec2norm()).fold(0.0, f64::max);
+    vectors.retain(|v| v.vec2norm() > LINEAR_TOL * rmax);
+    Subspace::new(&vectors, LINEAR_TOL)
+}
+
+/// Project the rigid-body translations and rotations out of `gradient` in
+/// place (Eckart projection), which removes any net force and torque.
+///
+/// With `masses`, `gradient` must be given in mass-weighted coordinates,
+/// i.e. each atom's gradient divided by sqrt(m).
+pub fn project_rigid_body_gradient(positions: &[[f64; 3]], masses: Option<&[f64]>, gradient: &mut [[f64; 3]]) {
+    assert_eq!(positions.len(), gradient.len(), "positions and gradient differ in size");
+    rigid_body_subspace(positions, masses).reject_in_place(gradient.as_mut_flat());
+}
+
+#[cfg(feature = "nalgebra")]
+/// Project the rigid-body translations and rotations out of a 3N x 3N
+/// Hessian (Eckart projection): (I - P) H (I - P).
+///
+/// With `masses`, `hessian` must be mass-weighted, i.e. H_ij / sqrt(m_i
+/// m_j), as used for vibrational frequencies.
+pub fn project_rigid_body_hessian(
+    positions: &[[f64; 3]],
+    masses: Option<&[f64]>,
+    hessian: &na::DMatrix<f64>,
+) -> na::DMatrix<f64> {
+    let n = 3 * positions.len();
+    assert_eq!(hessian.shape(), (n, n), "hessian is not a {n}x{n} matrix");
+    rigid_body_subspace(positions, masses).reject_matrix(hessian)
+}
+// 0f93b6e4 ends here
+
+// [[file:../vecfx.note::6da2c4f8][6da2c4f8]]
+#[test]
+fn test_eckart_projection() {
+    use approx::*;
+
+    // H2O
+    let positions = [[0.0, 0.0, 0.1173], [0.0, 0.7572, -0.4692], [0.0, -0.7572, -0.4692]];
+    let masses = [15.999, 1.008, 1.008];
+    assert_eq!(rigid_body_subspace(&positions, None).dim(), 6);
+    assert_eq!(rigid_body_subspace(&positions, Some(&masses)).dim(), 6);
+
+    // CO2 and a single atom
+    let co2 = [[0.0, 0.0, -1.16], [0.0, 0.0, 0.0], [0.0, 0.0, 1.16]];
+    assert_eq!(rigid_body_subspace(&co2, Some(&[15.999, 12.011, 15.999])).dim(), 5);
+    assert_eq!(rigid_body_subspace(&[[1.0, 2.0, 3.0]], None).dim(), 3);
+
+    // no net force or torque left
+    let mut gradient = [[0.3, -0.1, 0.2], [0.1, 0.5, -0.4], [-0.2, 0.3, 0.6]];
+    project_rigid_body_gradient(&positions, None, &mut gradient);
+    let centroid = [0.0, 0.0, (0.1173 - 2.0 * 0.4692) / 3.0];
+    let mut force = [0.0; 3];
+    let mut torque = [0.0; 3];
+    for (p, g) in positions.iter().zip(&gradient) {
+        force.vecadd(g, 1.0);
+        let r = [p[0] - centroid[0], p[1] - centroid[1], p[2] - centroid[2]];
+        torque.vecadd(
+            &[
+                r[1] * g[2] - r[2] * g[1],
+                r[2] * g[0] - r[0] * g[2],
+                r[0] * g[1] - r[1] * g[0],
+            ],
+            1.0,
+        );
+    }
+    assert_relative_eq!(force.vec2norm(), 0.0, epsilon = 1e-12);
+    assert_relative_eq!(torque.vec2norm(), 0.0, epsilon = 1e-12);
+
+    // a symmetric stretch has no net force or torque and is untouched
+    let stretch = [[0.0, 0.0, 0.1], [0.0, 0.1, -0.05], [0.0, -0.1, -0.05]];
+    let mut projected = stretch;
+    project_rigid_body_gradient(&positions, None, &mut projected);
+    assert_relative_eq!(projected.as_flat().vecdist(stretch.as_flat()), 0.0, epsilon = 1e-12);
+
+    #[cfg(feature = "nalgebra")]
+    {
+        let h = na::DMatrix::from_fn(9, 9, |i, j| 1.0 / (1 + i + j) as f64);
+        let hp = project_rigid_body_hessian(&positions, Some(&masses), &h);
+        for v in rigid_body_vectors(&positions, Some(&masses)) {
+            let hv = &hp * v.to_vector();
+            assert_relative_eq!(hv.norm(), 0.0, epsilon = 1e-10);
+        }
+    }
+}
+// 6da2c4f8 ends here
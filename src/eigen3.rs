@@ -0,0 +1,284 @@
+// [[file:../vecfx.note::6a0f2c8e][6a0f2c8e]]
+use crate::{Array3MathExt, ArrayMathExt, Float, Mat3, Mat3Ext, OrderedFloat};
+
+#[cfg(feature = "nalgebra")]
+use nalgebra as na;
+// 6a0f2c8e ends here
+
+// [[file:../vecfx.note::d41e7b93][d41e7b93]]
+/// Eigenvalues and eigenvectors of a symmetric 3x3 matrix.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymmetricEigen3<F = f64> {
+    /// Eigenvalues in ascending order.
+    pub eigenvalues: [F; 3],
+    /// Orthonormal eigenvectors stored as rows: `eigenvectors[i]` belongs to
+    /// `eigenvalues[i]`.
+    pub eigenvectors: Mat3<F>,
+}
+
+#[cfg(feature = "nalgebra")]
+impl<F: na::Scalar + Copy> SymmetricEigen3<F> {
+    /// Return the eigenvectors as columns of a nalgebra matrix, as in
+    /// `na::SymmetricEigen`.
+    pub fn eigenvector_matrix(&self) -> na::Matrix3<F> {
+        na::Matrix3::from_fn(|i, j| self.eigenvectors[j][i])
+    }
+}
+
+/// Eigen-decomposition of a symmetric 3x3 matrix, given as nested arrays or
+/// `Matrix3f`. Only the upper triangle is used.
+///
+/// The closed-form solution is used when its eigenpairs pass a residual
+/// check, and the Jacobi method otherwise, which is the case for nearly
+/// degenerate eigenvalues. A matrix with NaN entries gives NaN results
+/// instead of a panic.
+///
+/// # Example
+/// ```
+/// use vecfx::*;
+///
+/// let m = [[2.0, 1.0, 0.0], [1.0, 2.0, 0.0], [0.0, 0.0, 5.0]];
+/// let eigen = symmetric_eigen3(m);
+/// assert!((eigen.eigenvalues[0] - 1.0).abs() < 1e-12);
+/// assert!((eigen.eigenvalues[2] - 5.0).abs() < 1e-12);
+/// ```
+pub fn symmetric_eigen3<F>(m: impl Into<Mat3<F>>) -> SymmetricEigen3<F>
+where
+    F: Float + std::fmt::Debug,
+{
+    let a = symmetrized(m.into());
+    eigen3_analytic(&a).unwrap_or_else(|| eigen3_jacobi(&a))
+}
+
+/// Eigen-decomposition of a symmetric 3x3 matrix with the cyclic Jacobi
+/// method only. Slower than [`symmetric_eigen3`], but accurate to machine
+/// precision in all cases.
+pub fn symmetric_eigen3_jacobi<F>(m: impl Into<Mat3<F>>) -> SymmetricEigen3<F>
+where
+    F: Float + std::fmt::Debug,
+{
+    eigen3_jacobi(&symmetrized(m.into()))
+}
+
+/// Copy the upper triangle of `m` into the lower one.
+fn symmetrized<F: Float>(mut m: Mat3<F>) -> Mat3<F> {
+    m[1][0] = m[0][1];
+    m[2][0] = m[0][2];
+    m[2][1] = m[1][2];
+    m
+}
+
+fn small<F: Float>(x: f64) -> F {
+    F::from(x).unwrap() * F::epsilon()
+}
+
+/// Sort eigenpairs by eigenvalue.
+fn sorted<F: Float>(values: [F; 3], vectors: Mat3<F>) -> SymmetricEigen3<F> {
+    let mut idx = [0, 1, 2];
+    idx.sort_by_key(|&i| OrderedFloat(values[i]));
+    SymmetricEigen3 {
+        eigenvalues: idx.map(|i| values[i]),
+        eigenvectors: idx.map(|i| vectors[i]),
+    }
+}
+// d41e7b93 ends here
+
+// [[file:../vecfx.note::0c9b5e27][0c9b5e27]]
+/// Closed-form eigenvalues from the characteristic polynomial (Smith 1961),
+/// eigenvectors from cross products of the rows of A - lambda I. Returns
+/// `None` if the result is not accurate enough.
+fn eigen3_analytic<F: Float + std::fmt::Debug>(a: &Mat3<F>) -> Option<SymmetricEigen3<F>> {
+    let p1 = a[0][1].powi(2) + a[0][2].powi(2) + a[1][2].powi(2);
+    if p1.is_zero() {
+        let values = [a[0][0], a[1][1], a[2][2]];
+        return Some(sorted(values, crate::mat3_identity()));
+    }
+
+    let three = F::from(3).unwrap();
+    let q = a.trace() / three;
+    let p2 = (0..3).fold(p1 + p1, |s, i| s + (a[i][i] - q).powi(2));
+    let p = (p2 / F::from(6).unwrap()).sqrt();
+    let mut b = *a;
+    for (i, row) in b.iter_mut().enumerate() {
+        row[i] = row[i] - q;
+    }
+    let r = b.map(|row| row.array_scale(p.recip())).det() / (F::one() + F::one());
+    let phi = r.max(-F::one()).min(F::one()).acos() / three;
+    let shift = F::from(2.0 * std::f64::consts::FRAC_PI_3).unwrap();
+    let hi = q + (p + p) * phi.cos();
+    let lo = q + (p + p) * (phi + shift).cos();
+    let values = [lo, three * q - hi - lo, hi];
+
+    // start from the eigenvalue best separated from the middle one
+    let scale = lo.abs().max(hi.abs());
+    let (i, j) = if hi - values[1] >= values[1] - lo {
+        (2, 0)
+    } else {
+        (0, 2)
+    };
+    let vi = null_vector(a, values[i], scale)?;
+    let vj = match null_vector(a, values[j], scale) {
+        Some(v) => v.array_sub(vi.array_scale(v.array_dot(vi))).array_normalize()?,
+        // the other two eigenvalues are degenerate: any perpendicular pair
+        None => perpendicular(vi),
+    };
+    let mut vectors = [[F::zero(); 3]; 3];
+    vectors[i] = vi;
+    vectors[j] = vj;
+    vectors[1] = vi.array_cross(vj);
+
+    let tol = small::<F>(256.0) * scale;
+    for (v, &e) in vectors.iter().zip(&values) {
+        if a.matvec(*v).array_sub(v.array_scale(e)).array_norm() > tol {
+            return None;
+        }
+    }
+    Some(SymmetricEigen3 {
+        eigenvalues: values,
+        eigenvectors: vectors,
+    })
+}
+
+/// Unit vector in the null space of A - lambda I, taken as the longest cross
+/// product of two of its rows.
+fn null_vector<F: Float + std::fmt::Debug>(a: &Mat3<F>, lambda: F, scale: F) -> Option<[F; 3]> {
+    let mut b = *a;
+    for (i, row) in b.iter_mut().enumerate() {
+        row[i] = row[i] - lambda;
+    }
+    let v = [b[0].array_cross(b[1]), b[0].array_cross(b[2]), b[1].array_cross(b[2])]
+        .into_iter()
+        .max_by_key(|x| OrderedFloat(x.array_norm_squared()))?;
+    let norm = v.array_norm();
+    if norm <= small::<F>(16.0) * scale * scale {
+        None
+    } else {
+        Some(v.array_scale(norm.recip()))
+    }
+}
+
+/// A unit vector perpendicular to the unit vector `v`.
+fn perpendicular<F: Float + std::fmt::Debug>(v: [F; 3]) -> [F; 3] {
+    // cross with the axis least aligned with v
+    let k = (0..3).min_by_key(|&i| OrderedFloat(v[i].abs())).unwrap();
+    let mut axis = [F::zero(); 3];
+    axis[k] = F::one();
+    v.array_cross(axis).array_normalize().unwrap()
+}
+
+fn eigen3_jacobi<F: Float + std::fmt::Debug>(m: &Mat3<F>) -> SymmetricEigen3<F> {
+    let (values, vectors) = jacobi_eigen(m);
+    sorted(values, vectors)
+}
+
+/// Eigenvalues and eigenvectors (as rows, unsorted) of a small symmetric
+/// matrix by cyclic Jacobi rotations, until the off-diagonal elements
+/// vanish to machine precision.
+pub(crate) fn jacobi_eigen<F, const N: usize>(m: &[[F; N]; N]) -> ([F; N], [[F; N]; N])
+where
+    F: Float + std::fmt::Debug,
+{
+    let mut a = *m;
+    // columns of v are the eigenvectors
+    let mut v: [[F; N]; N] =
+        std::array::from_fn(|i| std::array::from_fn(|j| if i == j { F::one() } else { F::zero() }));
+    let norm2 = a.iter().fold(F::zero(), |s, row| s + row.array_norm_squared());
+    let tol2 = small::<F>(1.0).powi(2) * norm2;
+    for _ in 0..50 {
+        let off = (0..N).fold(F::zero(), |s, p| (p + 1..N).fold(s, |s, q| s + a[p][q].powi(2)));
+        if off <= tol2 {
+            break;
+        }
+        for p in 0..N {
+            for q in p + 1..N {
+                if a[p][q].is_zero() {
+                    continue;
+                }
+                let theta = (a[q][q] - a[p][p]) / (a[p][q] + a[p][q]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + F::one()).sqrt());
+                let c = (t * t + F::one()).sqrt().recip();
+                let s = t * c;
+                // A <- J^T A J
+                for k in 0..N {
+                    let (akp, akq) = (a[k][p], a[k][q]);
+                    a[k][p] = c * akp - s * akq;
+                    a[k][q] = s * akp + c * akq;
+                    let (vkp, vkq) = (v[k][p], v[k][q]);
+                    v[k][p] = c * vkp - s * vkq;
+                    v[k][q] = s * vkp + c * vkq;
+                }
+                let (rp, rq) = (a[p], a[q]);
+                a[p] = rp.array_scale(c).array_sub(rq.array_scale(s));
+                a[q] = rp.array_scale(s).array_add(rq.array_scale(c));
+                a[p][q] = F::zero();
+                a[q][p] = F::zero();
+            }
+        }
+    }
+
+    let values = std::array::from_fn(|i| a[i][i]);
+    let vectors = std::array::from_fn(|i| std::array::from_fn(|j| v[j][i]));
+    (values, vectors)
+}
+// 0c9b5e27 ends here
+
+// [[file:../vecfx.note::8f3d61ab][8f3d61ab]]
+#[test]
+fn test_symmetric_eigen3() {
+    use approx::*;
+
+    fn check(m: Mat3, eigen: &SymmetricEigen3) {
+        let scale = eigen.eigenvalues[2].abs().max(eigen.eigenvalues[0].abs());
+        for (v, &e) in eigen.eigenvectors.iter().zip(&eigen.eigenvalues) {
+            assert_relative_eq!(v.array_norm(), 1.0, epsilon = 1e-12);
+            let r = m.matvec(*v).array_sub(v.array_scale(e)).array_norm();
+            assert!(r <= 1e-12 * scale, "residual {r:e}");
+        }
+        let [v0, v1, v2] = eigen.eigenvectors;
+        assert_relative_eq!(v0.array_dot(v1), 0.0, epsilon = 1e-12);
+        assert_relative_eq!(v0.array_dot(v2), 0.0, epsilon = 1e-12);
+        assert!(eigen.eigenvalues[0] <= eigen.eigenvalues[1]);
+        assert!(eigen.eigenvalues[1] <= eigen.eigenvalues[2]);
+    }
+
+    let cases = [
+        // general
+        [[4.0, 1.0, -2.0], [1.0, 2.0, 0.5], [-2.0, 0.5, 3.0]],
+        // diagonal
+        [[3.0, 0.0, 0.0], [0.0, -1.0, 0.0], [0.0, 0.0, 2.0]],
+        // exactly and nearly degenerate, as for symmetric tops
+        [[2.0, 1.0, 1.0], [1.0, 2.0, 1.0], [1.0, 1.0, 2.0]],
+        [[1.0, 1e-9, 0.0], [1e-9, 1.0, 2e-10], [0.0, 2e-10, 5.0]],
+        [[1.0, 1e-13, 1e-13], [1e-13, 1.0 + 1e-12, 0.0], [1e-13, 0.0, 1.0]],
+        // zero matrix
+        [[0.0; 3]; 3],
+    ];
+    for m in cases {
+        let eigen = symmetric_eigen3(m);
+        check(m, &eigen);
+        let jacobi = symmetric_eigen3_jacobi(m);
+        check(m, &jacobi);
+        for (x, y) in eigen.eigenvalues.iter().zip(&jacobi.eigenvalues) {
+            assert_relative_eq!(x, y, epsilon = 1e-12);
+        }
+    }
+    let eigen = symmetric_eigen3(cases[2]);
+    assert_relative_eq!(eigen.eigenvalues[0], 1.0, epsilon = 1e-12);
+    assert_relative_eq!(eigen.eigenvalues[1], 1.0, epsilon = 1e-12);
+    assert_relative_eq!(eigen.eigenvalues[2], 4.0, epsilon = 1e-12);
+
+    // NaN entries must not panic
+    let nan = [[1.0, f64::NAN, 0.0], [f64::NAN, 2.0, 0.0], [0.0, 0.0, 3.0]];
+    assert!(symmetric_eigen3(nan).eigenvalues.iter().any(|x| x.is_nan()));
+    assert!(symmetric_eigen3_jacobi(nan).eigenvalues.iter().any(|x| x.is_nan()));
+
+    #[cfg(feature = "nalgebra")]
+    {
+        let m = crate::Matrix3f::from(cases[0]);
+        let eigen = symmetric_eigen3(m);
+        let v = eigen.eigenvector_matrix();
+        let d = na::Matrix3::from_diagonal(&na::Vector3::from(eigen.eigenvalues));
+        assert_relative_eq!(v * d * v.transpose(), m, epsilon = 1e-12);
+    }
+}
+// 8f3d61ab ends here
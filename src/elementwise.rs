@@ -0,0 +1,179 @@
+// [[file:../vecfx.note::9e2b6a04][9e2b6a04]]
+use crate::Float;
+// 9e2b6a04 ends here
+
+// [[file:../vecfx.note::5f81c3da][5f81c3da]]
+/// Elementwise operations on float slices.
+///
+/// Each operation comes in an in-place form working on self, and a `*_from`
+/// form writing the result into self as output buffer. All slices involved
+/// must have the same length, which is checked by debug assertions only.
+pub trait VecFloatElementwiseExt<F: Float = f64> {
+    /// y *= x
+    fn vecmul(&mut self, x: &[F]);
+
+    /// z = x * y
+    fn vecmul_from(&mut self, x: &[F], y: &[F]);
+
+    /// y /= x
+    fn vecdiv(&mut self, x: &[F]);
+
+    /// z = x / y
+    fn vecdiv_from(&mut self, x: &[F], y: &[F]);
+
+    /// y = |y|
+    fn vecabs(&mut self);
+
+    /// y = |x|
+    fn vecabs_from(&mut self, x: &[F]);
+
+    /// y = sqrt(y)
+    fn vecsqrt(&mut self);
+
+    /// y = sqrt(x)
+    fn vecsqrt_from(&mut self, x: &[F]);
+
+    /// Restrict each element of self to the interval `[lo, hi]`.
+    fn vecclamp(&mut self, lo: F, hi: F);
+
+    /// y = x clamped to the interval `[lo, hi]`
+    fn vecclamp_from(&mut self, x: &[F], lo: F, hi: F);
+
+    /// z = a*x + b*y + c*w, in a single pass
+    fn veclincomb(&mut self, a: F, x: &[F], b: F, y: &[F], c: F, w: &[F]);
+
+    /// y = f(y)
+    fn vecmap<M: Fn(F) -> F>(&mut self, f: M);
+
+    /// y = f(x)
+    fn vecmap_from<M: Fn(F) -> F>(&mut self, x: &[F], f: M);
+
+    /// z = f(x, y)
+    fn veczip_with<M: Fn(F, F) -> F>(&mut self, x: &[F], y: &[F], f: M);
+}
+
+impl<F: Float> VecFloatElementwiseExt<F> for [F] {
+    fn vecmul(&mut self, x: &[F]) {
+        zip_map(self, x, |a, b| a * b);
+    }
+
+    fn vecmul_from(&mut self, x: &[F], y: &[F]) {
+        self.veczip_with(x, y, |a, b| a * b);
+    }
+
+    fn vecdiv(&mut self, x: &[F]) {
+        zip_map(self, x, |a, b| a / b);
+    }
+
+    fn vecdiv_from(&mut self, x: &[F], y: &[F]) {
+        self.veczip_with(x, y, |a, b| a / b);
+    }
+
+    fn vecabs(&mut self) {
+        self.vecmap(F::abs);
+    }
+
+    fn vecabs_from(&mut self, x: &[F]) {
+        self.vecmap_from(x, F::abs);
+    }
+
+    fn vecsqrt(&mut self) {
+        self.vecmap(F::sqrt);
+    }
+
+    fn vecsqrt_from(&mut self, x: &[F]) {
+        self.vecmap_from(x, F::sqrt);
+    }
+
+    fn vecclamp(&mut self, lo: F, hi: F) {
+        self.vecmap(|v| v.max(lo).min(hi));
+    }
+
+    fn vecclamp_from(&mut self, x: &[F], lo: F, hi: F) {
+        self.vecmap_from(x, |v| v.max(lo).min(hi));
+    }
+
+    fn veclincomb(&mut self, a: F, x: &[F], b: F, y: &[F], c: F, w: &[F]) {
+        debug_assert_eq!(self.len(), x.len(), "veclincomb: dimension mismatch");
+        debug_assert_eq!(self.len(), y.len(), "veclincomb: dimension mismatch");
+        debug_assert_eq!(self.len(), w.len(), "veclincomb: dimension mismatch");
+        for (((z, &x), &y), &w) in self.iter_mut().zip(x).zip(y).zip(w) {
+            *z = a * x + b * y + c * w;
+        }
+    }
+
+    fn vecmap<M: Fn(F) -> F>(&mut self, f: M) {
+        for v in self.iter_mut() {
+            *v = f(*v);
+        }
+    }
+
+    fn vecmap_from<M: Fn(F) -> F>(&mut self, x: &[F], f: M) {
+        debug_assert_eq!(self.len(), x.len(), "vecmap_from: dimension mismatch");
+        for (v, &x) in self.iter_mut().zip(x) {
+            *v = f(x);
+        }
+    }
+
+    fn veczip_with<M: Fn(F, F) -> F>(&mut self, x: &[F], y: &[F], f: M) {
+        debug_assert_eq!(self.len(), x.len(), "veczip_with: dimension mismatch");
+        debug_assert_eq!(self.len(), y.len(), "veczip_with: dimension mismatch");
+        for ((z, &x), &y) in self.iter_mut().zip(x).zip(y) {
+            *z = f(x, y);
+        }
+    }
+}
+
+/// y = f(y, x)
+fn zip_map<F: Float, M: Fn(F, F) -> F>(y: &mut [F], x: &[F], f: M) {
+    debug_assert_eq!(y.len(), x.len(), "dimension mismatch");
+    for (y, &x) in y.iter_mut().zip(x) {
+        *y = f(*y, x);
+    }
+}
+// 5f81c3da ends here
+
+// [[file:../vecfx.note::0d4a7cb1][0d4a7cb1]]
+#[test]
+fn test_elementwise() {
+    let masses = [1.0, 4.0, 16.0];
+    let mut x = [2.0, -3.0, 0.5];
+    x.vecmul(&masses);
+    assert_eq!(x, [2.0, -12.0, 8.0]);
+    x.vecdiv(&masses);
+    assert_eq!(x, [2.0, -3.0, 0.5]);
+
+    let mut z = [0.0; 3];
+    z.vecmul_from(&x, &masses);
+    assert_eq!(z, [2.0, -12.0, 8.0]);
+    z.vecdiv_from(&masses, &[2.0; 3]);
+    assert_eq!(z, [0.5, 2.0, 8.0]);
+
+    z.vecsqrt_from(&masses);
+    assert_eq!(z, [1.0, 2.0, 4.0]);
+    z.vecsqrt();
+    assert_eq!(z, [1.0, 2.0_f64.sqrt(), 2.0]);
+
+    z.vecabs_from(&x);
+    assert_eq!(z, [2.0, 3.0, 0.5]);
+    x.vecabs();
+    assert_eq!(x, z);
+
+    x.vecclamp(0.6, 2.5);
+    assert_eq!(x, [2.0, 2.5, 0.6]);
+    z.vecclamp_from(&[-1.0, 0.0, 1.0], -0.5, 0.5);
+    assert_eq!(z, [-0.5, 0.0, 0.5]);
+
+    z.veclincomb(1.0, &[1.0; 3], 2.0, &[0.0, 1.0, 2.0], -1.0, &masses);
+    assert_eq!(z, [0.0, -1.0, -11.0]);
+
+    // masking
+    let mask = [1.0, 0.0, 1.0];
+    z.veczip_with(&[3.0; 3], &mask, |v, m| if m > 0.0 { v } else { 0.0 });
+    assert_eq!(z, [3.0, 0.0, 3.0]);
+    z.vecmap(|v| v * v);
+    assert_eq!(z, [9.0, 0.0, 9.0]);
+    z.vecmap_from(&masses, |v| v.ln());
+    assert_eq!(z[0], 0.0);
+}
+// 0d4a7cb1 ends here
@@ -0,0 +1,112 @@
+// [[file:../vecfx.note::a93c1e60][a93c1e60]]
+use crate::{mat3_outer, symmetric_eigen3, ArrayMathExt, Mat3, VecFloatNDExt};
+// a93c1e60 ends here
+
+// [[file:../vecfx.note::47d2b8fe][47d2b8fe]]
+/// Shape descriptors derived from the principal moments of the gyration
+/// tensor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GyrationShape {
+    /// Eigenvalues of the gyration tensor in ascending order.
+    pub principal_moments: [f64; 3],
+    /// Rg, the square root of the sum of the principal moments.
+    pub radius_of_gyration: f64,
+    /// b = l3 - (l1 + l2) / 2; zero for spherically symmetric shapes.
+    pub asphericity: f64,
+    /// c = l2 - l1; zero for cylindrically symmetric shapes.
+    pub acylindricity: f64,
+    /// k^2 = (b^2 + 3/4 c^2) / Rg^4, between 0 for spherical and 1 for
+    /// linear shapes.
+    pub relative_shape_anisotropy: f64,
+}
+
+/// Return the gyration tensor (1/W) sum(w (r - c)(r - c)^T) about the center
+/// c. With `masses` it is mass-weighted about the center of mass; without,
+/// all points have unit weight and c is the centroid.
+///
+/// # Panics
+///
+/// * if `masses` and `positions` differ in length.
+pub fn gyration_tensor(positions: &[[f64; 3]], masses: Option<&[f64]>) -> Mat3 {
+    let center = match masses {
+        Some(m) => positions.center_of_mass(m),
+        None => positions.centroid(),
+    };
+    let mut tensor = [[0.0; 3]; 3];
+    let mut wsum = 0.0;
+    for (i, p) in positions.iter().enumerate() {
+        let w = masses.map_or(1.0, |m| m[i]);
+        let r = p.array_sub(center);
+        let outer = mat3_outer(r, r);
+        for (row, o) in tensor.iter_mut().zip(outer) {
+            *row = row.array_add(o.array_scale(w));
+        }
+        wsum += w;
+    }
+    tensor.map(|row| row.array_scale(wsum.recip()))
+}
+
+/// Return the radius of gyration, mass-weighted with `masses`. See
+/// [`gyration_tensor`].
+pub fn radius_of_gyration(positions: &[[f64; 3]], masses: Option<&[f64]>) -> f64 {
+    let s = gyration_tensor(positions, masses);
+    (s[0][0] + s[1][1] + s[2][2]).sqrt()
+}
+
+/// Return shape descriptors from the gyration tensor. See
+/// [`gyration_tensor`] for `masses`.
+pub fn gyration_shape(positions: &[[f64; 3]], masses: Option<&[f64]>) -> GyrationShape {
+    let moments = symmetric_eigen3(gyration_tensor(positions, masses)).eigenvalues;
+    let [l1, l2, l3] = moments;
+    let rg2 = l1 + l2 + l3;
+    let b = l3 - 0.5 * (l1 + l2);
+    let c = l2 - l1;
+    GyrationShape {
+        principal_moments: moments,
+        radius_of_gyration: rg2.sqrt(),
+        asphericity: b,
+        acylindricity: c,
+        relative_shape_anisotropy: (b * b + 0.75 * c * c) / (rg2 * rg2),
+    }
+}
+// 47d2b8fe ends here
+
+// [[file:../vecfx.note::c5e8f241][c5e8f241]]
+#[test]
+fn test_gyration_shape() {
+    use approx::*;
+
+    // rod: all moments along the axis
+    let rod: Vec<_> = (0..5).map(|i| [0.0, 0.0, i as f64]).collect();
+    let shape = gyration_shape(&rod, None);
+    assert_relative_eq!(shape.radius_of_gyration, 2f64.sqrt(), epsilon = 1e-12);
+    assert_relative_eq!(shape.asphericity, 2.0, epsilon = 1e-12);
+    assert_relative_eq!(shape.acylindricity, 0.0, epsilon = 1e-12);
+    assert_relative_eq!(shape.relative_shape_anisotropy, 1.0, epsilon = 1e-12);
+
+    // octahedron: spherically symmetric
+    let octahedron = [
+        [1.0, 0.0, 0.0],
+        [-1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [0.0, -1.0, 0.0],
+        [0.0, 0.0, 1.0],
+        [0.0, 0.0, -1.0],
+    ];
+    let shape = gyration_shape(&octahedron, None);
+    assert_relative_eq!(shape.radius_of_gyration, 1.0, epsilon = 1e-12);
+    assert_relative_eq!(shape.asphericity, 0.0, epsilon = 1e-12);
+    assert_relative_eq!(shape.relative_shape_anisotropy, 0.0, epsilon = 1e-12);
+
+    // rectangle in a plane, mass-weighted
+    let rectangle = [[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [2.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+    let masses = [1.0, 3.0, 3.0, 1.0];
+    let shape = gyration_shape(&rectangle, Some(&masses));
+    assert_relative_eq!(shape.principal_moments[0], 0.0, epsilon = 1e-12);
+    assert_relative_eq!(shape.principal_moments[1], 0.25, epsilon = 1e-12);
+    assert_relative_eq!(shape.principal_moments[2], 0.75, epsilon = 1e-12);
+    assert_relative_eq!(shape.acylindricity, 0.25, epsilon = 1e-12);
+    assert_relative_eq!(radius_of_gyration(&rectangle, Some(&masses)), 1.0, epsilon = 1e-12);
+    assert_relative_eq!(radius_of_gyration(&rectangle, None), 1.25f64.sqrt(), epsilon = 1e-12);
+}
+// c5e8f241 ends here
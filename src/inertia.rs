@@ -0,0 +1,188 @@
+// [[file:../vecfx.note::5c8e02d7][5c8e02d7]]
+use crate::{symmetric_eigen3, Array3MathExt, ArrayMathExt, Mat3, Mat3Ext, VecFloatNDExt};
+// 5c8e02d7 ends here
+
+// [[file:../vecfx.note::b7a94f15][b7a94f15]]
+/// h / (8 pi^2) in GHz amu Å^2, to convert a moment of inertia in amu Å^2
+/// into a rotational constant in GHz.
+pub const ROTATIONAL_CONSTANT_GHZ: f64 = 505.379009141;
+
+/// Classification of a rigid rotor by its principal moments of inertia.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RotorType {
+    /// A single point, or all mass at one point.
+    Atom,
+    /// Ia = 0, Ib = Ic
+    Linear,
+    /// Ia = Ib = Ic
+    SphericalTop,
+    /// Ia < Ib = Ic
+    ProlateSymmetricTop,
+    /// Ia = Ib < Ic
+    OblateSymmetricTop,
+    /// Ia < Ib < Ic
+    AsymmetricTop,
+}
+
+/// Principal moments and axes of inertia of a set of point masses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrincipalAxes {
+    /// The center of mass.
+    pub center: [f64; 3],
+    /// Principal moments of inertia in ascending order: Ia <= Ib <= Ic.
+    pub moments: [f64; 3],
+    /// Principal axes stored as rows, one for each moment. They form a
+    /// right-handed frame, with signs fixed as in [`principal_axes`].
+    pub axes: Mat3,
+}
+
+impl PrincipalAxes {
+    /// Rotational constants A, B, C in GHz for positions in Å and masses
+    /// in amu. A vanishing moment gives an infinite constant.
+    pub fn rotational_constants(&self) -> [f64; 3] {
+        self.moments.map(|m| ROTATIONAL_CONSTANT_GHZ / m)
+    }
+
+    /// Classify the rotor. Moments are taken as equal or zero when they
+    /// differ by not more than `tol` times the largest moment.
+    pub fn rotor_type(&self, tol: f64) -> RotorType {
+        let [a, b, c] = self.moments;
+        let eps = tol * c;
+        if c <= eps || c == 0.0 {
+            RotorType::Atom
+        } else if a <= eps {
+            RotorType::Linear
+        } else if c - a <= eps {
+            RotorType::SphericalTop
+        } else if c - b <= eps {
+            RotorType::ProlateSymmetricTop
+        } else if b - a <= eps {
+            RotorType::OblateSymmetricTop
+        } else {
+            RotorType::AsymmetricTop
+        }
+    }
+
+    /// Return true if all masses lie in one plane (Ia + Ib = Ic), within
+    /// `tol` relative to the largest moment. Linear molecules are planar.
+    pub fn is_planar(&self, tol: f64) -> bool {
+        let [a, b, c] = self.moments;
+        (a + b - c).abs() <= tol * c
+    }
+}
+
+/// Return the inertia tensor of point masses about their center of mass:
+/// sum(m (r^2 I - r r^T)).
+///
+/// # Panics
+///
+/// * if `masses` and `positions` differ in length.
+pub fn inertia_tensor(positions: &[[f64; 3]], masses: &[f64]) -> Mat3 {
+    let center = positions.center_of_mass(masses);
+    let mut tensor = [[0.0; 3]; 3];
+    for (p, &m) in positions.iter().zip(masses) {
+        let r = p.array_sub(center);
+        let r2 = r.array_norm_squared();
+        for i in 0..3 {
+            for j in 0..3 {
+                let d = if i == j { r2 } else { 0.0 };
+                tensor[i][j] += m * (d - r[i] * r[j]);
+            }
+        }
+    }
+    tensor
+}
+
+/// Return the principal moments and axes of inertia of point masses.
+///
+/// The sign of each axis is chosen deterministically: the third moment
+/// sum(m x^3) of the coordinates along the first two axes is made
+/// positive, or, if it vanishes by symmetry, the first coordinate that
+/// does not vanish. The third axis completes a right-handed frame. Axes of
+/// degenerate moments, as for symmetric tops, are determined only up to a
+/// rotation about the unique axis.
+pub fn principal_axes(positions: &[[f64; 3]], masses: &[f64]) -> PrincipalAxes {
+    let center = positions.center_of_mass(masses);
+    let eigen = symmetric_eigen3(inertia_tensor(positions, masses));
+    let mut axes = eigen.eigenvectors;
+    let size = positions.iter().fold(0.0, |s: f64, p| s.max(p.array_distance(center)));
+    let tol = 1e-8 * size;
+    for axis in axes.iter_mut().take(2) {
+        let xs: Vec<f64> = positions.iter().map(|p| p.array_sub(center).array_dot(*axis)).collect();
+        let skew: f64 = xs.iter().zip(masses).map(|(x, m)| m * x.powi(3)).sum();
+        let scale: f64 = xs.iter().zip(masses).map(|(x, m)| m * x.abs().powi(3)).sum();
+        let sign = if skew.abs() > 1e-8 * scale {
+            skew
+        } else {
+            xs.iter().copied().find(|x| x.abs() > tol).unwrap_or(1.0)
+        };
+        if sign < 0.0 {
+            *axis = axis.array_neg();
+        }
+    }
+    axes[2] = axes[0].array_cross(axes[1]);
+
+    PrincipalAxes {
+        center,
+        moments: eigen.eigenvalues,
+        axes,
+    }
+}
+
+/// Move the center of mass to the origin and rotate the principal axes onto
+/// x, y and z in place, giving a canonical orientation of the structure.
+/// Returns the principal axes before the transformation.
+pub fn orient_principal_axes(positions: &mut [[f64; 3]], masses: &[f64]) -> PrincipalAxes {
+    let principal = principal_axes(positions, masses);
+    positions.translate(principal.center.array_neg());
+    principal.axes.apply_to(positions);
+    principal
+}
+// b7a94f15 ends here
+
+// [[file:../vecfx.note::2e6fd3b0][2e6fd3b0]]
+#[test]
+fn test_principal_axes() {
+    use approx::*;
+
+    // H2O
+    let mut positions = [[0.0, 0.0, 0.1173], [0.0, 0.7572, -0.4692], [0.0, -0.7572, -0.4692]];
+    let masses = [15.999, 1.008, 1.008];
+    let principal = principal_axes(&positions, &masses);
+    assert_eq!(principal.rotor_type(1e-4), RotorType::AsymmetricTop);
+    assert!(principal.is_planar(1e-8));
+    let [a, b, c] = principal.rotational_constants();
+    assert!(a > b && b > c);
+    assert_relative_eq!(a, 820.6, epsilon = 0.1);
+
+    // canonical orientation does not depend on the input orientation
+    let rot = [[0.36, 0.48, -0.80], [-0.80, 0.60, 0.00], [0.48, 0.64, 0.60]];
+    let mut rotated = positions;
+    rot.apply_to(&mut rotated);
+    rotated.translate([1.0, -2.0, 3.0]);
+    orient_principal_axes(&mut positions, &masses);
+    orient_principal_axes(&mut rotated, &masses);
+    for (p, q) in positions.iter().zip(&rotated) {
+        assert_relative_eq!(p.array_distance(*q), 0.0, epsilon = 1e-10);
+    }
+    let tensor = inertia_tensor(&positions, &masses);
+    assert_relative_eq!(tensor[0][1], 0.0, epsilon = 1e-10);
+    assert_relative_eq!(tensor[1][2], 0.0, epsilon = 1e-10);
+    assert_relative_eq!(tensor[0][0], principal.moments[0], epsilon = 1e-10);
+    assert_relative_eq!(positions.center_of_mass(&masses).array_norm(), 0.0, epsilon = 1e-12);
+
+    // linear, spherical and symmetric tops
+    let co2 = [[0.0, 0.0, -1.16], [0.0, 0.0, 0.0], [0.0, 0.0, 1.16]];
+    let classify = |positions: &[[f64; 3]]| principal_axes(positions, &vec![1.0; positions.len()]).rotor_type(1e-6);
+    assert_eq!(classify(&co2), RotorType::Linear);
+    assert_eq!(classify(&[[1.0, 2.0, 3.0]]), RotorType::Atom);
+    let tetrahedron = [[1.0, 1.0, 1.0], [1.0, -1.0, -1.0], [-1.0, 1.0, -1.0], [-1.0, -1.0, 1.0]];
+    assert_eq!(classify(&tetrahedron), RotorType::SphericalTop);
+    let s = 3f64.sqrt() / 2.0;
+    let triangle = [[1.0, 0.0, 0.0], [-0.5, s, 0.0], [-0.5, -s, 0.0]];
+    assert_eq!(classify(&triangle), RotorType::OblateSymmetricTop);
+    let mut prism = triangle.to_vec();
+    prism.extend(triangle.map(|p| [p[0], p[1], 3.0]));
+    assert_eq!(classify(&prism), RotorType::ProlateSymmetricTop);
+}
+// 2e6fd3b0 ends here
@@ -3,7 +3,6 @@
 pub(crate) mod na {
     use crate::nalgebra as na;
     use crate::vector::*;
-    use crate::Matrix3f;
 
     pub trait FloatIterToVector {
         fn to_vector(self) -> na::DVector<f64>;
@@ -51,7 +50,6 @@ pub(crate) mod na {
 // na matrix:1 ends here
 
 // [[file:~/Workspace/Programming/gchemol-rs/vecfx/vecfx.note::*impl][impl:1]]
-use std::borrow::Borrow;
 use std::f64;
 
 // https://stackoverflow.com/questions/43921436/extend-iterator-with-a-mean-method
@@ -110,16 +108,16 @@ mod tests {
 
     #[test]
     fn test_float_ordering() {
-        let values = vec![1.0, -1.0, 0.0 / 0.0, 0.5, 2.0];
+        let values = [1.0, -1.0, f64::NAN, 0.5, 2.0];
         assert_eq!(values.iter().float_max(), values.max());
         assert_eq!(values.iter().float_min(), values.min());
 
-        let values = vec![1.0, -1.0, 0.0 / 0.0, 0.5, 2.0];
+        let values = [1.0, -1.0, f64::NAN, 0.5, 2.0];
         assert_eq!(values.iter().float_max(), values.max());
         assert_eq!(values.iter().float_min(), values.min());
 
         // test for compatibility
-        let values = vec![1.0f64, 2.0, 0.0, -9.0, 0.0 / 0.0];
+        let values = [1.0f64, 2.0, 0.0, -9.0, f64::NAN];
         assert_eq!(values.iter().cloned().float_max(), values.max());
         assert_eq!(values.iter().cloned().float_min(), values.min());
     }
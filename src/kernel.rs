@@ -0,0 +1,233 @@
+// [[file:../vecfx.note::71c4e0d9][71c4e0d9]]
+//! Hot loops behind `VecFloatExt`, written with independent accumulators so
+//! that the compiler can keep several SIMD lanes busy. Floating point addition
+//! is not associative, so a plain `zip().map().sum()` reduction is compiled to
+//! a strictly sequential chain of adds. Splitting the sum over `LANES`
+//! partial accumulators breaks that dependency chain explicitly.
+//!
+//! The reductions therefore sum in a different order than the naive scalar
+//! loop. Both orders have the same worst-case rounding error bound, so for
+//! `n` terms the two results differ by at most about `2 * n * eps * sum(|x_i
+//! * y_i|)`, where `eps` is the machine epsilon of the float type.
+//!
+//! With the `rayon` feature, slices longer than `par::THRESHOLD` are split
+//! into chunks of a fixed size that are processed in parallel. The partial
+//! results are combined in chunk order, so the result does not depend on the
+//! number of threads.
+
+use crate::Float;
+// 71c4e0d9 ends here
+
+// [[file:../vecfx.note::c6e2a9d4][c6e2a9d4]]
+/// The float types the vector kernels work on. `Send + Sync` lets the
+/// `rayon` feature share slices across threads; every `Float` type, such as
+/// `f32` or `f64`, satisfies it. The bound is the same with or without
+/// `rayon`, so enabling the feature does not change any public impl.
+///
+/// # Example
+/// ```
+/// use vecfx::*;
+///
+/// fn norm<F: KernelFloat + std::fmt::Debug + 'static>(x: &[F]) -> F {
+///     x.vec2norm()
+/// }
+/// assert_eq!(norm(&[3.0f32, 4.0]), 5.0);
+/// ```
+pub trait KernelFloat: Float + Send + Sync {}
+
+impl<F: Float + Send + Sync> KernelFloat for F {}
+// c6e2a9d4 ends here
+
+// [[file:../vecfx.note::2a9f5b83][2a9f5b83]]
+/// Number of independent partial accumulators. Eight f64 fill two AVX2
+/// registers or one AVX-512 register.
+const LANES: usize = 8;
+
+/// Sum of `f(x_i, y_i)` using `LANES` partial accumulators.
+#[inline(always)]
+fn reduce2<F: Float>(x: &[F], y: &[F], f: impl Fn(F, F) -> F) -> F {
+    let n = x.len().min(y.len());
+    let (x, y) = (&x[..n], &y[..n]);
+
+    let mut acc = [F::zero(); LANES];
+    let xc = x.chunks_exact(LANES);
+    let yc = y.chunks_exact(LANES);
+    let (xr, yr) = (xc.remainder(), yc.remainder());
+    for (a, b) in xc.zip(yc) {
+        for k in 0..LANES {
+            acc[k] = acc[k] + f(a[k], b[k]);
+        }
+    }
+
+    // pairwise combine of the partial sums
+    let mut width = LANES;
+    while width > 1 {
+        width /= 2;
+        for k in 0..width {
+            acc[k] = acc[k] + acc[k + width];
+        }
+    }
+
+    xr.iter().zip(yr).fold(acc[0], |s, (&a, &b)| s + f(a, b))
+}
+
+/// s = x·y
+pub(crate) fn dot<F: KernelFloat>(x: &[F], y: &[F]) -> F {
+    #[cfg(feature = "rayon")]
+    if x.len() >= par::THRESHOLD {
+        return par::map_chunks2(x, y, |a, b| reduce2(a, b, |a, b| a * b))
+            .into_iter()
+            .fold(F::zero(), |s, p| s + p);
+    }
+    reduce2(x, y, |a, b| a * b)
+}
+
+/// s = ||x - y||^2
+pub(crate) fn dist_squared<F: KernelFloat>(x: &[F], y: &[F]) -> F {
+    #[cfg(feature = "rayon")]
+    if x.len() >= par::THRESHOLD {
+        return par::map_chunks2(x, y, |a, b| reduce2(a, b, |a, b| (a - b) * (a - b)))
+            .into_iter()
+            .fold(F::zero(), |s, p| s + p);
+    }
+    reduce2(x, y, |a, b| (a - b) * (a - b))
+}
+
+/// y += c*x
+pub(crate) fn axpy<F: KernelFloat>(y: &mut [F], x: &[F], c: F) {
+    #[cfg(feature = "rayon")]
+    if y.len() >= par::THRESHOLD {
+        use rayon::prelude::*;
+        y.par_chunks_mut(par::CHUNK)
+            .zip(x.par_chunks(par::CHUNK))
+            .for_each(|(b, a)| axpy_serial(b, a, c));
+        return;
+    }
+    axpy_serial(y, x, c)
+}
+
+fn axpy_serial<F: Float>(y: &mut [F], x: &[F], c: F) {
+    let n = x.len().min(y.len());
+    let (x, y) = (&x[..n], &mut y[..n]);
+
+    let mut yc = y.chunks_exact_mut(LANES);
+    let mut xc = x.chunks_exact(LANES);
+    for (b, a) in (&mut yc).zip(&mut xc) {
+        for k in 0..LANES {
+            b[k] = b[k] + c * a[k];
+        }
+    }
+    for (b, &a) in yc.into_remainder().iter_mut().zip(xc.remainder()) {
+        *b = *b + c * a;
+    }
+}
+
+/// y *= c
+pub(crate) fn scale<F: KernelFloat>(y: &mut [F], c: F) {
+    #[cfg(feature = "rayon")]
+    if y.len() >= par::THRESHOLD {
+        use rayon::prelude::*;
+        y.par_chunks_mut(par::CHUNK).for_each(|b| scale_serial(b, c));
+        return;
+    }
+    scale_serial(y, c)
+}
+
+fn scale_serial<F: Float>(y: &mut [F], c: F) {
+    for v in y.iter_mut() {
+        *v = *v * c;
+    }
+}
+// 2a9f5b83 ends here
+
+// [[file:../vecfx.note::e6b47d21][e6b47d21]]
+#[cfg(feature = "rayon")]
+pub(crate) mod par {
+    use rayon::prelude::*;
+
+    /// Slices shorter than this are always processed serially.
+    pub(crate) const THRESHOLD: usize = 1 << 18;
+
+    /// Fixed chunk size for parallel work, independent of the thread count.
+    pub(crate) const CHUNK: usize = 1 << 15;
+
+    /// Apply `f` to fixed-size chunks of `x` in parallel. Results are
+    /// returned in chunk order.
+    pub(crate) fn map_chunks<T, R, G>(x: &[T], f: G) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        G: Fn(&[T]) -> R + Sync + Send,
+    {
+        x.par_chunks(CHUNK).map(f).collect()
+    }
+
+    /// Apply `f` to matching fixed-size chunks of `x` and `y` in parallel.
+    /// Results are returned in chunk order.
+    pub(crate) fn map_chunks2<T, R, G>(x: &[T], y: &[T], f: G) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        G: Fn(&[T], &[T]) -> R + Sync + Send,
+    {
+        x.par_chunks(CHUNK).zip(y.par_chunks(CHUNK)).map(|(a, b)| f(a, b)).collect()
+    }
+}
+// e6b47d21 ends here
+
+// [[file:../vecfx.note::c3e8d150][c3e8d150]]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn naive_dot(x: &[f64], y: &[f64]) -> f64 {
+        x.iter().zip(y).map(|(a, b)| a * b).sum()
+    }
+
+    #[test]
+    fn test_kernels_vs_scalar() {
+        for n in [0, 1, 7, 8, 9, 31, 1000, 1003] {
+            let x: Vec<f64> = (0..n).map(|i| ((i * 7919) % 113) as f64 / 17.0 - 3.0).collect();
+            let y: Vec<f64> = (0..n).map(|i| ((i * 104729) % 97) as f64 / 13.0 - 2.5).collect();
+
+            let bound = 2.0 * n as f64 * f64::EPSILON * x.iter().zip(&y).map(|(a, b)| (a * b).abs()).sum::<f64>();
+            assert!((dot(&x, &y) - naive_dot(&x, &y)).abs() <= bound);
+
+            let d: Vec<f64> = x.iter().zip(&y).map(|(a, b)| a - b).collect();
+            let bound = 2.0 * n as f64 * f64::EPSILON * naive_dot(&d, &d);
+            assert!((dist_squared(&x, &y) - naive_dot(&d, &d)).abs() <= bound);
+
+            let mut z = y.clone();
+            axpy(&mut z, &x, 0.5);
+            for i in 0..n {
+                assert_eq!(z[i], y[i] + 0.5 * x[i]);
+            }
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_kernels_deterministic() {
+        let n = par::THRESHOLD * 3 + 17;
+        let x: Vec<f64> = (0..n).map(|i| ((i * 7919) % 113) as f64 / 17.0 - 3.0).collect();
+        let y: Vec<f64> = (0..n).map(|i| ((i * 104729) % 97) as f64 / 13.0 - 2.5).collect();
+
+        let run = |nthreads| {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(nthreads).build().unwrap();
+            pool.install(|| {
+                let mut z = y.clone();
+                axpy(&mut z, &x, 0.5);
+                scale(&mut z, 2.0);
+                (dot(&x, &y), dist_squared(&x, &y), z)
+            })
+        };
+        let (d1, s1, z1) = run(1);
+        for nthreads in [2, 3, 8] {
+            let (d, s, z) = run(nthreads);
+            assert_eq!(d.to_bits(), d1.to_bits());
+            assert_eq!(s.to_bits(), s1.to_bits());
+            assert_eq!(z, z1);
+        }
+    }
+}
+// c3e8d150 ends here
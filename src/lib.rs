@@ -1,36 +1,75 @@
 //! Backend for vector operations
 
-// [[file:../vecfx.note::*imports][imports:1]]
-use std::f64;
-// imports:1 ends here
-
 // [[file:../vecfx.note::*mods][mods:1]]
-#[cfg(feature = "nalgebra")]
 mod projection;
 #[cfg(feature = "nalgebra")]
 #[cfg(feature = "adhoc")]
 mod rotation;
 
 mod array;
+mod assignment;
+mod bbox;
+mod convergence;
+mod eckart;
+mod eigen3;
+mod elementwise;
+mod gyration;
+mod inertia;
 mod iterator;
+mod kernel;
+mod mat3;
+mod minimize;
+mod norm;
 mod ord;
+mod orthonormal;
 mod stats;
+mod superpose;
+mod vec3;
 mod vector;
 // mods:1 ends here
 
 // [[file:../vecfx.note::ecfb2b0c][ecfb2b0c]]
 pub use crate::stats::*;
 
-#[cfg(feature = "nalgebra")]
 pub use projection::*;
 
+pub use crate::eckart::*;
+
 pub use crate::vector::*;
 
+pub use crate::vec3::*;
+
+pub use crate::mat3::*;
+
+pub use crate::eigen3::*;
+
+pub use crate::inertia::*;
+
+pub use crate::gyration::*;
+
+pub use crate::bbox::*;
+
+pub use crate::superpose::*;
+
+pub use crate::assignment::*;
+
+pub use crate::elementwise::*;
+
+pub use crate::norm::*;
+
+pub use crate::orthonormal::*;
+
 #[cfg(feature = "nalgebra")]
 pub use nalgebra;
 
 pub use crate::iterator::FloatIterExt;
 
+pub use crate::kernel::KernelFloat;
+
+pub use crate::minimize::*;
+
+pub use crate::convergence::*;
+
 #[cfg(feature = "nalgebra")]
 pub use crate::iterator::na::*;
 
@@ -42,6 +81,4 @@ pub use crate::ord::*;
 
 pub use array::*;
 
-// ordered floats
-pub use ord::*;
 // ecfb2b0c ends here
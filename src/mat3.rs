@@ -0,0 +1,119 @@
+// [[file:../vecfx.note::2d6c9f41][2d6c9f41]]
+use crate::{Array3MathExt, ArrayMathExt, Float};
+// 2d6c9f41 ends here
+
+// [[file:../vecfx.note::8e15b7a3][8e15b7a3]]
+/// A 3x3 matrix as plain nested arrays, stored row by row: `m[i][j]` is the
+/// element in row `i` and column `j`.
+pub type Mat3<F = f64> = [[F; 3]; 3];
+
+/// The 3x3 identity matrix.
+pub fn mat3_identity<F: Float>() -> Mat3<F> {
+    let (o, l) = (F::zero(), F::one());
+    [[l, o, o], [o, l, o], [o, o, l]]
+}
+
+/// The outer product `a b^T`.
+pub fn mat3_outer<F: Float>(a: [F; 3], b: [F; 3]) -> Mat3<F> {
+    a.map(|x| b.map(|y| x * y))
+}
+
+/// Simple 3x3 matrix math on nested arrays, without nalgebra.
+pub trait Mat3Ext<F: Float = f64> {
+    /// Matrix product self * other.
+    fn matmul(&self, other: &Mat3<F>) -> Mat3<F>;
+
+    /// Matrix-vector product self * v.
+    fn matvec(&self, v: [F; 3]) -> [F; 3];
+
+    /// Return the transposed matrix.
+    fn transpose(&self) -> Mat3<F>;
+
+    /// The determinant.
+    fn det(&self) -> F;
+
+    /// The sum of the diagonal elements.
+    fn trace(&self) -> F;
+
+    /// Return the inverse matrix, or `None` if self is singular.
+    fn inverse(&self) -> Option<Mat3<F>>;
+
+    /// Replace each point `p` with `self * p` in place, e.g. to rotate a
+    /// set of positions or convert fractional to Cartesian coordinates.
+    fn apply_to(&self, points: &mut [[F; 3]]);
+}
+
+impl<F: Float + std::fmt::Debug> Mat3Ext<F> for Mat3<F> {
+    fn matmul(&self, other: &Mat3<F>) -> Mat3<F> {
+        let t = other.transpose();
+        self.map(|row| t.map(|col| row.array_dot(col)))
+    }
+
+    fn matvec(&self, v: [F; 3]) -> [F; 3] {
+        self.map(|row| row.array_dot(v))
+    }
+
+    fn transpose(&self) -> Mat3<F> {
+        std::array::from_fn(|i| std::array::from_fn(|j| self[j][i]))
+    }
+
+    fn det(&self) -> F {
+        self[0].array_dot(self[1].array_cross(self[2]))
+    }
+
+    fn trace(&self) -> F {
+        self[0][0] + self[1][1] + self[2][2]
+    }
+
+    fn inverse(&self) -> Option<Mat3<F>> {
+        let det = self.det();
+        if det.is_zero() || !det.is_finite() {
+            return None;
+        }
+        // the columns of the inverse are the cross products of the rows
+        let cols = [
+            self[1].array_cross(self[2]),
+            self[2].array_cross(self[0]),
+            self[0].array_cross(self[1]),
+        ];
+        Some(cols.transpose().map(|row| row.array_scale(det.recip())))
+    }
+
+    fn apply_to(&self, points: &mut [[F; 3]]) {
+        for p in points.iter_mut() {
+            *p = self.matvec(*p);
+        }
+    }
+}
+// 8e15b7a3 ends here
+
+// [[file:../vecfx.note::f3a0c582][f3a0c582]]
+#[test]
+fn test_mat3() {
+    use approx::*;
+
+    let m = [[2.0, 0.0, 1.0], [1.0, 3.0, 0.0], [0.0, 1.0, 4.0]];
+    assert_eq!(m.det(), 25.0);
+    assert_eq!(m.trace(), 9.0);
+    assert_eq!(m.transpose()[0], [2.0, 1.0, 0.0]);
+    assert_eq!(m.matvec([1.0, 1.0, 1.0]), [3.0, 4.0, 5.0]);
+    assert_eq!(m.matmul(&mat3_identity()), m);
+
+    let inv = m.inverse().unwrap();
+    let id: Mat3 = mat3_identity();
+    for (r, e) in m.matmul(&inv).iter().zip(&id) {
+        assert_relative_eq!(r.array_distance(*e), 0.0, epsilon = 1e-12);
+    }
+
+    let u = mat3_outer([1.0, 2.0, 3.0], [1.0, 0.0, -1.0]);
+    assert_eq!(u[1], [2.0, 0.0, -2.0]);
+    assert_eq!(u.det(), 0.0);
+    assert_eq!(u.inverse(), None);
+
+    // rotation by 90 degrees about z
+    let rot = [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]];
+    let mut points = [[1.0, 0.0, 0.0], [0.0, 2.0, 3.0]];
+    rot.apply_to(&mut points);
+    assert_eq!(points, [[0.0, 1.0, 0.0], [-2.0, 0.0, 3.0]]);
+}
+// f3a0c582 ends here
@@ -0,0 +1,430 @@
+// [[file:../vecfx.note::3c9a71e2][3c9a71e2]]
+use crate::{VecFloatExt, VecFloatNDExt};
+// 3c9a71e2 ends here
+
+// [[file:../vecfx.note::5d0e8b46][5d0e8b46]]
+/// Zero out the rows of `forces` for atoms flagged in `frozen`. An empty
+/// `frozen` mask freezes nothing.
+///
+/// # Panics
+///
+/// * if `frozen` is not empty and differs in length from `forces`.
+fn apply_frozen_mask(forces: &mut [[f64; 3]], frozen: &[bool]) {
+    assert!(
+        frozen.is_empty() || frozen.len() == forces.len(),
+        "frozen mask has {} entries for {} atoms",
+        frozen.len(),
+        forces.len()
+    );
+    for (f, &fixed) in forces.iter_mut().zip(frozen) {
+        if fixed {
+            *f = [0.0; 3];
+        }
+    }
+}
+
+/// Scale the whole displacement down uniformly so that no atom moves more
+/// than `max_step`. Uniform scaling keeps the search direction unchanged.
+fn cap_displacement(disp: &mut [[f64; 3]], max_step: f64) {
+    let dmax = max_atom_norm(disp);
+    if dmax > max_step {
+        disp.as_mut_flat().vecscale(max_step / dmax);
+    }
+}
+
+/// The largest per-atom norm of a list of 3D vectors, e.g. the max force.
+fn max_atom_norm(vectors: &[[f64; 3]]) -> f64 {
+    vectors.iter().map(|v| v.vec2norm()).fold(0.0, f64::max)
+}
+
+/// Summary returned after a minimization run.
+#[derive(Debug, Clone, Copy)]
+pub struct MinimizeOutput {
+    /// The number of iterations performed.
+    pub niter: usize,
+    /// The energy at the final positions.
+    pub energy: f64,
+    /// The largest force on any movable atom at the final positions.
+    pub fmax: f64,
+    /// Whether `fmax` fell below the requested threshold.
+    pub converged: bool,
+}
+// 5d0e8b46 ends here
+
+// [[file:../vecfx.note::a87f13d5][a87f13d5]]
+/// The fast inertial relaxation engine (FIRE) of Bitzek et al., Phys. Rev.
+/// Lett. 97, 170201 (2006).
+///
+/// FIRE only needs forces, so it can be driven step by step with projected
+/// forces (e.g. in NEB) using [`Fire::step`], or run to convergence with
+/// [`Fire::minimize`].
+///
+/// # Example
+/// ```
+/// use vecfx::*;
+///
+/// let mut fire = Fire {
+///     max_step: 0.1,
+///     frozen: vec![false, true],
+///     ..Default::default()
+/// };
+/// let mut positions = [[0.5, 0.0, 0.0], [1.0, 1.0, 1.0]];
+/// let out = fire.minimize(&mut positions, 1e-6, 1000, |x, f| {
+///     f[0] = x[0].map(|v| -v);
+///     f[1] = [1.0; 3];
+///     0.5 * x[0].iter().map(|v| v * v).sum::<f64>()
+/// });
+/// assert!(out.converged);
+/// assert_eq!(positions[1], [1.0, 1.0, 1.0]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Fire {
+    /// Initial time step.
+    pub dt: f64,
+    /// Upper bound for the time step.
+    pub dt_max: f64,
+    /// Number of uphill-free steps before the time step may grow.
+    pub n_min: usize,
+    /// Time step increase factor.
+    pub f_inc: f64,
+    /// Time step decrease factor.
+    pub f_dec: f64,
+    /// Initial velocity mixing parameter.
+    pub alpha_start: f64,
+    /// Decay factor for the mixing parameter.
+    pub f_alpha: f64,
+    /// Maximum displacement of a single atom per step.
+    pub max_step: f64,
+    /// Atoms flagged `true` are kept fixed. Empty means all atoms are free.
+    pub frozen: Vec<bool>,
+    /// Velocities and adaptive parameters carried between steps.
+    pub state: FireState,
+}
+
+/// The run state of [`Fire`], cleared by [`Fire::reset`].
+#[derive(Debug, Clone, Default)]
+pub struct FireState {
+    velocity: Vec<[f64; 3]>,
+    alpha: f64,
+    dt_now: f64,
+    n_pos: usize,
+}
+
+impl Default for Fire {
+    fn default() -> Self {
+        Self {
+            dt: 0.1,
+            dt_max: 1.0,
+            n_min: 5,
+            f_inc: 1.1,
+            f_dec: 0.5,
+            alpha_start: 0.1,
+            f_alpha: 0.99,
+            max_step: 0.2,
+            frozen: vec![],
+            state: FireState::default(),
+        }
+    }
+}
+
+impl Fire {
+    /// Forget the accumulated velocities and restart from the initial
+    /// parameters.
+    pub fn reset(&mut self) {
+        self.state.velocity.clear();
+    }
+
+    /// Move `positions` one FIRE step along `forces`, the negative gradient
+    /// evaluated at `positions`.
+    ///
+    /// # Panics
+    ///
+    /// * if `positions` and `forces` have different lengths.
+    pub fn step(&mut self, positions: &mut [[f64; 3]], forces: &[[f64; 3]]) {
+        assert_eq!(positions.len(), forces.len(), "positions and forces differ in size");
+
+        let mut forces = forces.to_vec();
+        apply_frozen_mask(&mut forces, &self.frozen);
+        let f = forces.as_flat();
+
+        let state = &mut self.state;
+        if state.velocity.len() != positions.len() {
+            state.velocity = vec![[0.0; 3]; positions.len()];
+            state.alpha = self.alpha_start;
+            state.dt_now = self.dt;
+            state.n_pos = 0;
+        } else {
+            let v = state.velocity.as_mut_flat();
+            let power = f.vecdot(v);
+            if power > 0.0 {
+                // v = (1-a) v + a |v| f/|f|
+                let vnorm = v.vec2norm();
+                let fnorm = f.vec2norm();
+                v.vecscale(1.0 - state.alpha);
+                if fnorm > 0.0 {
+                    v.vecadd(f, state.alpha * vnorm / fnorm);
+                }
+                if state.n_pos > self.n_min {
+                    state.dt_now = (state.dt_now * self.f_inc).min(self.dt_max);
+                    state.alpha *= self.f_alpha;
+                }
+                state.n_pos += 1;
+            } else {
+                v.vecscale(0.0);
+                state.alpha = self.alpha_start;
+                state.dt_now *= self.f_dec;
+                state.n_pos = 0;
+            }
+        }
+
+        // Euler step
+        state.velocity.as_mut_flat().vecadd(f, state.dt_now);
+        let mut disp = state.velocity.clone();
+        disp.as_mut_flat().vecscale(state.dt_now);
+        cap_displacement(&mut disp, self.max_step);
+        positions.as_mut_flat().vecadd(disp.as_flat(), 1.0);
+    }
+
+    /// Relax `positions` in place until the max force drops below `fmax` or
+    /// `max_iter` steps have been taken.
+    ///
+    /// `f` computes the energy at the given positions and writes the forces
+    /// into its second argument.
+    pub fn minimize<F>(&mut self, positions: &mut [[f64; 3]], fmax: f64, max_iter: usize, mut f: F) -> MinimizeOutput
+    where
+        F: FnMut(&[[f64; 3]], &mut [[f64; 3]]) -> f64,
+    {
+        self.reset();
+        let mut forces = vec![[0.0; 3]; positions.len()];
+        let mut energy = f(positions, &mut forces);
+        apply_frozen_mask(&mut forces, &self.frozen);
+        let mut niter = 0;
+        while max_atom_norm(&forces) >= fmax && niter < max_iter {
+            self.step(positions, &forces);
+            energy = f(positions, &mut forces);
+            apply_frozen_mask(&mut forces, &self.frozen);
+            niter += 1;
+        }
+
+        let fmax_now = max_atom_norm(&forces);
+        MinimizeOutput {
+            niter,
+            energy,
+            fmax: fmax_now,
+            converged: fmax_now < fmax,
+        }
+    }
+}
+// a87f13d5 ends here
+
+// [[file:../vecfx.note::e41b6c0f][e41b6c0f]]
+/// Search direction update used by [`ConjugateGradient`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CgMethod {
+    /// Always search along the forces.
+    SteepestDescent,
+    /// beta = f'·f' / f·f
+    FletcherReeves,
+    /// beta = max(0, f'·(f' - f) / f·f), i.e. PR+
+    PolakRibiere,
+}
+
+/// Steepest descent and nonlinear conjugate gradient minimization with a
+/// backtracking (Armijo) line search.
+#[derive(Debug, Clone)]
+pub struct ConjugateGradient {
+    /// How to combine the new forces with the previous search direction.
+    pub method: CgMethod,
+    /// Maximum displacement of a single atom per line search trial.
+    pub max_step: f64,
+    /// Sufficient decrease parameter of the Armijo condition.
+    pub c1: f64,
+    /// Maximum number of backtracking trials per line search.
+    pub max_linesearch: usize,
+    /// Atoms flagged `true` are kept fixed. Empty means all atoms are free.
+    pub frozen: Vec<bool>,
+}
+
+impl Default for ConjugateGradient {
+    fn default() -> Self {
+        Self {
+            method: CgMethod::PolakRibiere,
+            max_step: 0.2,
+            c1: 1e-4,
+            max_linesearch: 20,
+            frozen: vec![],
+        }
+    }
+}
+
+impl ConjugateGradient {
+    /// Create a minimizer using `method` with default settings.
+    pub fn new(method: CgMethod) -> Self {
+        Self {
+            method,
+            ..Default::default()
+        }
+    }
+
+    /// Relax `positions` in place until the max force drops below `fmax` or
+    /// `max_iter` iterations have been taken.
+    ///
+    /// `f` computes the energy at the given positions and writes the forces
+    /// into its second argument.
+    pub fn minimize<F>(&self, positions: &mut [[f64; 3]], fmax: f64, max_iter: usize, mut f: F) -> MinimizeOutput
+    where
+        F: FnMut(&[[f64; 3]], &mut [[f64; 3]]) -> f64,
+    {
+        let n = positions.len();
+        let mut forces = vec![[0.0; 3]; n];
+        let mut energy = f(positions, &mut forces);
+        apply_frozen_mask(&mut forces, &self.frozen);
+
+        let mut direction = forces.clone();
+        let mut trial = vec![[0.0; 3]; n];
+        let mut trial_forces = vec![[0.0; 3]; n];
+        let mut niter = 0;
+        while max_atom_norm(&forces) >= fmax && niter < max_iter {
+            // restart along the forces if not a descent direction
+            let mut slope = forces.as_flat().vecdot(direction.as_flat());
+            if slope <= 0.0 {
+                direction.as_mut_flat().veccpy(forces.as_flat());
+                slope = forces.as_flat().vecdot(direction.as_flat());
+            }
+
+            // a vanishing direction means vanishing forces: nothing to do
+            let dmax = max_atom_norm(&direction);
+            if dmax == 0.0 {
+                break;
+            }
+
+            // backtracking line search along `direction`, starting from a
+            // step moving the farthest atom by `max_step`
+            let mut step = self.max_step / dmax;
+            let mut trial_energy = energy;
+            for _ in 0..self.max_linesearch {
+                trial.as_mut_flat().veccpy(positions.as_flat());
+                trial.as_mut_flat().vecadd(direction.as_flat(), step);
+                trial_energy = f(&trial, &mut trial_forces);
+                if trial_energy <= energy - self.c1 * step * slope {
+                    break;
+                }
+                step *= 0.5;
+            }
+            // false if either energy is NaN
+            let downhill = trial_energy <= energy;
+            if !downhill {
+                // no progress along the search direction
+                break;
+            }
+            apply_frozen_mask(&mut trial_forces, &self.frozen);
+
+            let ff_old = forces.as_flat().vecdot(forces.as_flat());
+            let ff_new = trial_forces.as_flat().vecdot(trial_forces.as_flat());
+            let beta = match self.method {
+                CgMethod::SteepestDescent => 0.0,
+                CgMethod::FletcherReeves => ff_new / ff_old,
+                CgMethod::PolakRibiere => {
+                    let ff_mix = trial_forces.as_flat().vecdot(forces.as_flat());
+                    ((ff_new - ff_mix) / ff_old).max(0.0)
+                }
+            };
+            // d = f' + beta * d
+            direction.as_mut_flat().vecscale(beta);
+            direction.as_mut_flat().vecadd(trial_forces.as_flat(), 1.0);
+
+            positions.as_mut_flat().veccpy(trial.as_flat());
+            forces.as_mut_flat().veccpy(trial_forces.as_flat());
+            energy = trial_energy;
+            niter += 1;
+        }
+
+        let fmax_now = max_atom_norm(&forces);
+        MinimizeOutput {
+            niter,
+            energy,
+            fmax: fmax_now,
+            converged: fmax_now < fmax,
+        }
+    }
+}
+// e41b6c0f ends here
+
+// [[file:../vecfx.note::0b62d97a][0b62d97a]]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::*;
+
+    // anisotropic harmonic wells centered at `[i, 0, 0]` for atom `i`
+    fn harmonic(positions: &[[f64; 3]], forces: &mut [[f64; 3]]) -> f64 {
+        let k = [1.0, 4.0, 9.0];
+        let mut energy = 0.0;
+        for (i, (p, f)) in positions.iter().zip(forces.iter_mut()).enumerate() {
+            let d = [p[0] - i as f64, p[1], p[2]];
+            for j in 0..3 {
+                energy += 0.5 * k[j] * d[j] * d[j];
+                f[j] = -k[j] * d[j];
+            }
+        }
+        energy
+    }
+
+    #[test]
+    fn test_fire() {
+        let mut positions = [[0.3, 0.2, -0.1], [1.5, -0.4, 0.2], [1.8, 0.1, 0.3]];
+        let mut fire = Fire {
+            frozen: vec![false, true, false],
+            ..Default::default()
+        };
+        let out = fire.minimize(&mut positions, 1e-5, 1000, harmonic);
+        assert!(out.converged);
+        assert_relative_eq!(positions[0][0], 0.0, epsilon = 1e-4);
+        assert_relative_eq!(positions[2][0], 2.0, epsilon = 1e-4);
+        // frozen atom stays in place
+        assert_eq!(positions[1], [1.5, -0.4, 0.2]);
+    }
+
+    #[test]
+    fn test_cg() {
+        for method in [
+            CgMethod::SteepestDescent,
+            CgMethod::FletcherReeves,
+            CgMethod::PolakRibiere,
+        ] {
+            let mut positions = [[0.3, 0.2, -0.1], [1.5, -0.4, 0.2], [1.8, 0.1, 0.3]];
+            let cg = ConjugateGradient::new(method);
+            let out = cg.minimize(&mut positions, 1e-5, 1000, harmonic);
+            assert!(out.converged, "{:?}", method);
+            assert_relative_eq!(out.energy, 0.0, epsilon = 1e-8);
+            assert_relative_eq!(positions[1][1], 0.0, epsilon = 1e-4);
+        }
+
+        // already at the minimum, with a zero force threshold
+        let mut positions = [[0.0; 3]];
+        let out = ConjugateGradient::default().minimize(&mut positions, 0.0, 10, harmonic);
+        assert_eq!(positions, [[0.0; 3]]);
+        assert_eq!(out.niter, 0);
+
+        // stop instead of accepting a NaN energy
+        let mut positions = [[0.3, 0.2, -0.1]];
+        let out = ConjugateGradient::default().minimize(&mut positions, 1e-5, 10, |x, f| {
+            harmonic(x, f);
+            f64::NAN
+        });
+        assert_eq!(positions, [[0.3, 0.2, -0.1]]);
+        assert!(!out.converged);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_frozen_mask_size() {
+        let mut positions = [[0.3, 0.2, -0.1], [1.5, -0.4, 0.2]];
+        let cg = ConjugateGradient {
+            frozen: vec![true],
+            ..Default::default()
+        };
+        cg.minimize(&mut positions, 1e-5, 10, harmonic);
+    }
+}
+// 0b62d97a ends here
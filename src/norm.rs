@@ -0,0 +1,160 @@
+// [[file:../vecfx.note::b3d0f7a2][b3d0f7a2]]
+use crate::kernel::KernelFloat;
+use crate::{Float, VecFloatExt};
+// b3d0f7a2 ends here
+
+// [[file:../vecfx.note::61e9a4c8][61e9a4c8]]
+/// Vector norms other than the plain L2 norm, and normalization.
+pub trait VecFloatNormExt<F: Float = f64> {
+    /// ||x||_1 = sum(|x_i|)
+    fn vec1norm(&self) -> F;
+
+    /// ||x||_inf = max(|x_i|). Returns zero for an empty slice.
+    fn vecinfnorm(&self) -> F;
+
+    /// ||x||_p = sum(|x_i|^p)^(1/p), for `p >= 1`. `p = F::infinity()` gives
+    /// the maximum norm.
+    ///
+    /// # Panics
+    ///
+    /// * if `p < 1`.
+    fn vecpnorm(&self, p: F) -> F;
+
+    /// sqrt(sum(w_i * x_i^2)), the norm with a diagonal metric `w`, e.g.
+    /// mass-weighted norm with each mass repeated for the x, y and z
+    /// components.
+    fn vecwnorm(&self, w: &[F]) -> F;
+
+    /// sqrt(x^T W x) with a full metric `W` given as n x n row-major slice.
+    ///
+    /// # Panics
+    ///
+    /// * if `metric` does not have `n * n` elements.
+    fn vecmnorm(&self, metric: &[F]) -> F;
+
+    /// Scale self to unit L2 norm in place, returning the original norm. The
+    /// norm is computed as in [`VecFloatExt::vecnrm2`], so large components
+    /// do not overflow. A zero vector, or one whose norm is not finite, is
+    /// left untouched and `None` is returned.
+    fn vecnormalize(&mut self) -> Option<F>;
+
+    /// Return a copy of self scaled to unit L2 norm, or `None` for a zero
+    /// vector or a non-finite norm.
+    fn vecnormalized(&self) -> Option<Vec<F>>;
+}
+
+impl<F> VecFloatNormExt<F> for [F]
+where
+    F: KernelFloat + std::fmt::Debug + 'static,
+{
+    fn vec1norm(&self) -> F {
+        self.vecasum()
+    }
+
+    fn vecinfnorm(&self) -> F {
+        self.iter().fold(F::zero(), |m, x| m.max(x.abs()))
+    }
+
+    fn vecpnorm(&self, p: F) -> F {
+        assert!(p >= F::one(), "vecpnorm: p must not be less than 1");
+        if p == F::one() {
+            self.vec1norm()
+        } else if p == F::one() + F::one() {
+            self.vec2norm()
+        } else if p.is_infinite() {
+            self.vecinfnorm()
+        } else {
+            // scale by the largest component to avoid overflow
+            let m = self.vecinfnorm();
+            if m.is_zero() {
+                return m;
+            }
+            let s = self.iter().fold(F::zero(), |s, x| s + (x.abs() / m).powf(p));
+            m * s.powf(p.recip())
+        }
+    }
+
+    fn vecwnorm(&self, w: &[F]) -> F {
+        debug_assert_eq!(self.len(), w.len(), "vecwnorm: dimension mismatch");
+        self.iter().zip(w).fold(F::zero(), |s, (&x, &w)| s + w * x * x).sqrt()
+    }
+
+    fn vecmnorm(&self, metric: &[F]) -> F {
+        let n = self.len();
+        assert_eq!(metric.len(), n * n, "vecmnorm: metric is not a {n}x{n} matrix");
+        metric
+            .chunks_exact(n.max(1))
+            .zip(self)
+            .fold(F::zero(), |s, (row, &x)| s + x * row.vecdot(self))
+            .sqrt()
+    }
+
+    fn vecnormalize(&mut self) -> Option<F> {
+        let norm = self.vecnrm2();
+        if norm.is_zero() || !norm.is_finite() {
+            None
+        } else {
+            // divide rather than scale by the reciprocal, which overflows
+            // for a subnormal norm
+            for x in self.iter_mut() {
+                *x = *x / norm;
+            }
+            Some(norm)
+        }
+    }
+
+    fn vecnormalized(&self) -> Option<Vec<F>> {
+        let mut v = self.to_vec();
+        v.vecnormalize().map(|_| v)
+    }
+}
+// 61e9a4c8 ends here
+
+// [[file:../vecfx.note::f27c5e19][f27c5e19]]
+#[test]
+fn test_vec_norms() {
+    use approx::*;
+
+    let x = [3.0, -4.0, 0.0];
+    assert_eq!(x.vec1norm(), 7.0);
+    assert_eq!(x.vecinfnorm(), 4.0);
+    assert_eq!(x.vecpnorm(1.0), 7.0);
+    assert_eq!(x.vecpnorm(2.0), 5.0);
+    assert_eq!(x.vecpnorm(f64::INFINITY), 4.0);
+    assert_relative_eq!(x.vecpnorm(3.0), 91f64.powf(1.0 / 3.0), epsilon = 1e-12);
+
+    let w = [1.0, 4.0, 2.0];
+    assert_relative_eq!(x.vecwnorm(&w), 73f64.sqrt());
+    #[rustfmt::skip]
+    let metric = [
+        1.0, 0.0, 0.0,
+        0.0, 4.0, 0.0,
+        0.0, 0.0, 2.0,
+    ];
+    assert_relative_eq!(x.vecmnorm(&metric), x.vecwnorm(&w));
+    let metric = [2.0, 1.0, 1.0, 2.0];
+    assert_relative_eq!([1.0, 1.0].vecmnorm(&metric), 6f64.sqrt());
+
+    let mut y = x;
+    assert_eq!(y.vecnormalize(), Some(5.0));
+    assert_relative_eq!(y[1], -0.8);
+    assert_eq!(x.vecnormalized().unwrap(), y);
+
+    let mut z = [0.0; 3];
+    assert_eq!(z.vecnormalize(), None);
+    assert_eq!(z, [0.0; 3]);
+    assert_eq!(z.vecnormalized(), None);
+
+    // no overflow in the norm of large components
+    let mut big = [1e200, 0.0];
+    assert_eq!(big.vecnormalize(), Some(1e200));
+    assert_eq!(big, [1.0, 0.0]);
+    let mut tiny = [1e-309, 0.0];
+    assert_eq!(tiny.vecnormalize(), Some(1e-309));
+    assert_eq!(tiny, [1.0, 0.0]);
+    assert_eq!([0.0, -5e-324].vecnormalized(), Some(vec![0.0, -1.0]));
+    let mut inf = [f64::INFINITY, 1.0];
+    assert_eq!(inf.vecnormalize(), None);
+    assert_eq!(inf, [f64::INFINITY, 1.0]);
+}
+// f27c5e19 ends here
@@ -1,6 +1,4 @@
 // [[file:../vecfx.note::be82ddff][be82ddff]]
-use std::borrow::Borrow;
-
 pub use ordered_float::{Float, OrderedFloat};
 
 /// Convert a float number to OrderedFloat type
@@ -8,6 +6,7 @@ pub trait AsOrderedFloatExt<F>
 where
     F: Float,
 {
+    #[allow(clippy::wrong_self_convention)]
     fn as_ordered_float(self) -> OrderedFloat<F>;
 }
 
@@ -37,14 +36,14 @@ impl<F: Float> SortByExt for [F] {
 // [[file:../vecfx.note::edd3e54f][edd3e54f]]
 #[test]
 fn test_float_ord() {
-    let mut values = vec![1.0, -1.0, 2.0];
+    let mut values = [1.0, -1.0, 2.0];
     let mut values_ordered: Vec<_> = values.iter().map(|x| x.as_ordered_float()).collect();
     values_ordered.sort();
     assert_eq!(values_ordered[0], -1.0);
     assert_eq!(values_ordered[2], 2.0);
 
     values.sort_by_float();
-    assert_eq!(values[0], values_ordered[0].into());
-    assert_eq!(values[1], values_ordered[1].into());
+    assert_eq!(values[0], values_ordered[0].into_inner());
+    assert_eq!(values[1], values_ordered[1].into_inner());
 }
 // edd3e54f ends here
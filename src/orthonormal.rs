@@ -0,0 +1,216 @@
+// [[file:../vecfx.note::c90f1e57][c90f1e57]]
+use crate::kernel::KernelFloat;
+use crate::VecFloatExt;
+
+#[cfg(feature = "nalgebra")]
+use nalgebra as na;
+// c90f1e57 ends here
+
+// [[file:../vecfx.note::4be62d18][4be62d18]]
+/// Variants of the Gram-Schmidt process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GramSchmidt {
+    /// Project each vector against the original vector, all at once.
+    /// Fast, but loses orthogonality for ill-conditioned input.
+    Classical,
+    /// Project out each basis vector in turn from the running residual.
+    Modified,
+    /// Modified Gram-Schmidt applied twice, which gives orthogonality to
+    /// machine precision ("twice is enough").
+    ModifiedReorthogonalized,
+}
+
+/// The orthonormal basis produced by [`orthonormalize`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Orthonormalized<F = f64> {
+    /// Orthonormal vectors spanning the same space as the input.
+    pub basis: Vec<Vec<F>>,
+    /// Indices of the input vectors that were kept, one per basis vector.
+    pub indices: Vec<usize>,
+}
+
+impl<F> Orthonormalized<F> {
+    /// The numerical rank of the input vectors.
+    pub fn rank(&self) -> usize {
+        self.basis.len()
+    }
+}
+
+/// Orthonormalize a set of vectors with the Gram-Schmidt process.
+///
+/// A vector is dropped as linearly dependent when the norm of its component
+/// orthogonal to the vectors accepted before it is not larger than `tol`
+/// times its own norm. Zero vectors are always dropped.
+///
+/// # Panics
+///
+/// * if the vectors differ in length.
+///
+/// # Example
+/// ```
+/// use vecfx::*;
+///
+/// let vectors = [[1.0, 1.0, 0.0], [2.0, 2.0, 0.0], [0.0, 1.0, 1.0]];
+/// let ortho = orthonormalize(&vectors, GramSchmidt::ModifiedReorthogonalized, 1e-8);
+/// assert_eq!(ortho.rank(), 2);
+/// assert_eq!(ortho.indices, [0, 2]);
+/// ```
+pub fn orthonormalize<F, V>(vectors: &[V], method: GramSchmidt, tol: F) -> Orthonormalized<F>
+where
+    F: KernelFloat + std::fmt::Debug + 'static,
+    V: AsRef<[F]>,
+{
+    let mut basis: Vec<Vec<F>> = vec![];
+    let mut indices = vec![];
+    for (i, v) in vectors.iter().enumerate() {
+        let v = v.as_ref();
+        if let Some(b) = basis.first() {
+            assert_eq!(b.len(), v.len(), "orthonormalize: vectors differ in length");
+        }
+        let norm0 = v.vec2norm();
+        if norm0.is_zero() {
+            continue;
+        }
+
+        let mut r = v.to_vec();
+        match method {
+            GramSchmidt::Classical => {
+                let coefs: Vec<F> = basis.iter().map(|q| q.vecdot(v)).collect();
+                for (q, c) in basis.iter().zip(coefs) {
+                    r.vecadd(q, -c);
+                }
+            }
+            GramSchmidt::Modified => project_out(&mut r, &basis),
+            GramSchmidt::ModifiedReorthogonalized => {
+                project_out(&mut r, &basis);
+                project_out(&mut r, &basis);
+            }
+        }
+
+        let norm = r.vec2norm();
+        if norm > tol * norm0 {
+            r.vecscale(norm.recip());
+            basis.push(r);
+            indices.push(i);
+        }
+    }
+
+    Orthonormalized { basis, indices }
+}
+
+/// Remove the components along each orthonormal vector in `basis` from `r`,
+/// one at a time.
+fn project_out<F>(r: &mut [F], basis: &[Vec<F>])
+where
+    F: KernelFloat + std::fmt::Debug + 'static,
+{
+    for q in basis {
+        let c = q.vecdot(r);
+        r.vecadd(q, -c);
+    }
+}
+// 4be62d18 ends here
+
+// [[file:../vecfx.note::a1d7c63f][a1d7c63f]]
+#[cfg(feature = "nalgebra")]
+/// Orthonormalize the columns of `m` with the Gram-Schmidt process. Returns
+/// the orthonormal columns and the indices of the columns kept. See
+/// [`orthonormalize`] for the meaning of `tol`.
+pub fn orthonormalize_columns(m: &na::DMatrix<f64>, method: GramSchmidt, tol: f64) -> (na::DMatrix<f64>, Vec<usize>) {
+    let columns: Vec<&[f64]> = m.as_slice().chunks_exact(m.nrows().max(1)).collect();
+    let ortho = orthonormalize(&columns, method, tol);
+    let flat: Vec<f64> = ortho.basis.concat();
+    let q = na::DMatrix::from_column_slice(m.nrows(), ortho.rank(), &flat);
+    (q, ortho.indices)
+}
+
+#[cfg(feature = "nalgebra")]
+/// Orthonormalize the columns of `m` from its QR decomposition. Columns with
+/// `|R_ii|` not larger than `tol` times the column norm are treated as
+/// linearly dependent and dropped. Returns the orthonormal columns and the
+/// indices of the columns kept.
+///
+/// Without column pivoting, QR detects dependencies in column order, like the
+/// Gram-Schmidt process.
+pub fn orthonormalize_qr(m: &na::DMatrix<f64>, tol: f64) -> (na::DMatrix<f64>, Vec<usize>) {
+    // Householder QR without pivoting fills the Q column of a dependent
+    // column with an arbitrary direction, which spoils the span of the
+    // following columns. So drop the first dependent column and redo the
+    // decomposition until none is left.
+    let mut indices: Vec<usize> = (0..m.ncols()).filter(|&i| m.column(i).norm() > 0.0).collect();
+    loop {
+        if indices.is_empty() {
+            return (na::DMatrix::zeros(m.nrows(), 0), indices);
+        }
+        // at most nrows columns can be independent
+        let k = indices.len().min(m.nrows());
+        let columns: Vec<_> = indices[..k].iter().map(|&i| m.column(i)).collect();
+        let a = na::DMatrix::from_columns(&columns);
+        let qr = a.clone().qr();
+        let r = qr.r();
+        match (0..k).find(|&i| r[(i, i)].abs() <= tol * a.column(i).norm()) {
+            Some(i) => {
+                indices.remove(i);
+            }
+            None => {
+                indices.truncate(k);
+                let q = qr.q();
+                let q = q.columns(0, k).into_owned();
+                return (q, indices);
+            }
+        }
+    }
+}
+// a1d7c63f ends here
+
+// [[file:../vecfx.note::3ef06b29][3ef06b29]]
+#[test]
+fn test_orthonormalize() {
+    use approx::*;
+
+    let vectors = vec![
+        vec![1.0, 2.0, 0.0, 1.0],
+        vec![0.0, 0.0, 0.0, 0.0],
+        vec![2.0, 4.0, 0.0, 2.0 + 1e-12],
+        vec![0.0, 1.0, 1.0, 0.0],
+        vec![1.0, 3.0, 1.0, 1.0],
+        vec![0.3, -0.2, 0.5, 0.9],
+    ];
+    for method in [
+        GramSchmidt::Classical,
+        GramSchmidt::Modified,
+        GramSchmidt::ModifiedReorthogonalized,
+    ] {
+        let ortho = orthonormalize(&vectors, method, 1e-8);
+        assert_eq!(ortho.rank(), 3);
+        assert_eq!(ortho.indices, [0, 3, 5]);
+        for i in 0..3 {
+            for j in 0..3 {
+                let e = if i == j { 1.0 } else { 0.0 };
+                assert_relative_eq!(ortho.basis[i].vecdot(&ortho.basis[j]), e, epsilon = 1e-12);
+            }
+        }
+    }
+
+    #[cfg(feature = "nalgebra")]
+    {
+        let flat: Vec<f64> = vectors.concat();
+        let m = na::DMatrix::from_column_slice(4, vectors.len(), &flat);
+        let (q, indices) = orthonormalize_columns(&m, GramSchmidt::Modified, 1e-8);
+        assert_eq!(indices, [0, 3, 5]);
+        assert_relative_eq!(q.transpose() * &q, na::DMatrix::identity(3, 3), epsilon = 1e-12);
+
+        let m = m.remove_column(1);
+        let (q, indices) = orthonormalize_qr(&m, 1e-8);
+        assert_eq!(indices, [0, 2, 4]);
+        assert_relative_eq!(q.transpose() * &q, na::DMatrix::identity(3, 3), epsilon = 1e-12);
+
+        // no nonzero columns
+        for m in [na::DMatrix::zeros(4, 2), na::DMatrix::zeros(4, 0)] {
+            let (q, indices) = orthonormalize_qr(&m, 1e-8);
+            assert_eq!(q.shape(), (4, 0));
+            assert!(indices.is_empty());
+        }
+    }
+}
+// 3ef06b29 ends here
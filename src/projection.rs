@@ -1,10 +1,29 @@
 // [[file:../vecfx.note::bc0ea816][bc0ea816]]
-use crate::{VecFloatExt, Vector3fVec};
+use crate::kernel::KernelFloat;
+use crate::{Float, VecFloatExt};
 
+#[cfg(feature = "nalgebra")]
+use crate::Vector3fVec;
+
+#[cfg(feature = "nalgebra")]
 use nalgebra as na;
 // bc0ea816 ends here
 
 // [[file:../vecfx.note::*base][base:1]]
+/// Projection of vector `va` (self) onto vector `vb`.
+///
+/// The plain methods assume non-zero inputs and silently return NaN
+/// otherwise. The `checked_*` variants return `None` when a vector that must
+/// be non-zero has a norm not larger than `tol`:
+///
+/// * `checked_cosine_similarity` requires both `va` and `vb` to be non-zero,
+///   and clamps the result to `[-1, 1]`;
+/// * the projection and rejection methods only require `vb` to be non-zero.
+///   A zero `va` has a zero projection and a zero rejection.
+///
+/// For nearly parallel inputs the rejection is tiny and dominated by rounding
+/// errors of order `f64::EPSILON * |va|`; compare its norm against a
+/// tolerance rather than against zero.
 pub trait VectorProject {
     fn cosine_similarity(&self, vb: &Self) -> f64;
 
@@ -13,10 +32,28 @@ pub trait VectorProject {
     fn vector_projection(&self, vb: &Self) -> Self;
 
     fn vector_rejection(&self, vb: &Self) -> Self;
+
+    fn checked_cosine_similarity(&self, vb: &Self, tol: f64) -> Option<f64>;
+
+    fn checked_scalar_projection(&self, vb: &Self, tol: f64) -> Option<f64>;
+
+    fn checked_vector_projection(&self, vb: &Self, tol: f64) -> Option<Self>
+    where
+        Self: Sized;
+
+    fn checked_vector_rejection(&self, vb: &Self, tol: f64) -> Option<Self>
+    where
+        Self: Sized;
+
+    /// The angle between `va` and `vb` in radians, in the range `[0, pi]`.
+    fn angle_between(&self, vb: &Self) -> f64 {
+        self.cosine_similarity(vb).clamp(-1.0, 1.0).acos()
+    }
 }
 // base:1 ends here
 
 // [[file:../vecfx.note::1f0f248b][1f0f248b]]
+#[cfg(feature = "nalgebra")]
 macro_rules! impl_vector_project {
     ($type_name:ty) => {
         impl VectorProject for $type_name {
@@ -39,17 +76,319 @@ macro_rules! impl_vector_project {
             fn vector_rejection(&self, vb: &Self) -> Self {
                 self - &self.vector_projection(vb)
             }
+
+            fn checked_cosine_similarity(&self, vb: &Self, tol: f64) -> Option<f64> {
+                let (na, nb) = (self.norm(), vb.norm());
+                if na <= tol || nb <= tol {
+                    return None;
+                }
+                Some((self.dot(vb) / (na * nb)).clamp(-1.0, 1.0))
+            }
+
+            fn checked_scalar_projection(&self, vb: &Self, tol: f64) -> Option<f64> {
+                let nb = vb.norm();
+                if nb <= tol {
+                    return None;
+                }
+                Some(self.dot(vb) / nb)
+            }
+
+            fn checked_vector_projection(&self, vb: &Self, tol: f64) -> Option<Self> {
+                let nb = vb.norm();
+                if nb <= tol {
+                    return None;
+                }
+                Some(vb * (self.dot(vb) / (nb * nb)))
+            }
+
+            fn checked_vector_rejection(&self, vb: &Self, tol: f64) -> Option<Self> {
+                Some(self - &self.checked_vector_projection(vb, tol)?)
+            }
         }
     };
 }
 
+#[cfg(feature = "nalgebra")]
 impl_vector_project!(na::DVector<f64>);
+#[cfg(feature = "nalgebra")]
 impl_vector_project!(Vector3fVec);
 // 1f0f248b ends here
 
+// [[file:../vecfx.note::7a3e5f10][7a3e5f10]]
+/// Vector projection and rejection on plain float slices, without nalgebra.
+pub trait VectorProjectSlice<F: Float = f64> {
+    /// The cosine of two non-zero vectors `va` and `vb`
+    fn cosine_similarity(&self, vb: &[F]) -> F;
+
+    /// the scalar projection of a vector a onto a vector b
+    fn scalar_projection(&self, vb: &[F]) -> F;
+
+    /// The vector component of `va` parallel to `vb`
+    fn vector_projection(&self, vb: &[F]) -> Vec<F>;
+
+    /// The vector component of `va` perpendicular to `vb`
+    fn vector_rejection(&self, vb: &[F]) -> Vec<F>;
+
+    /// The angle between `va` and `vb` in radians, in the range `[0, pi]`.
+    fn angle_between(&self, vb: &[F]) -> F;
+
+    /// See [`VectorProject`] for the semantics of the `checked_*` methods.
+    fn checked_cosine_similarity(&self, vb: &[F], tol: F) -> Option<F>;
+
+    fn checked_scalar_projection(&self, vb: &[F], tol: F) -> Option<F>;
+
+    fn checked_vector_projection(&self, vb: &[F], tol: F) -> Option<Vec<F>>;
+
+    fn checked_vector_rejection(&self, vb: &[F], tol: F) -> Option<Vec<F>>;
+
+    /// The angle between `va` and `vb`, or `None` if either is a zero vector.
+    fn checked_angle_between(&self, vb: &[F], tol: F) -> Option<F>;
+}
+
+impl<F> VectorProjectSlice<F> for [F]
+where
+    F: KernelFloat + std::fmt::Debug + 'static,
+{
+    fn cosine_similarity(&self, vb: &[F]) -> F {
+        self.vecdot(vb) / (self.vec2norm() * vb.vec2norm())
+    }
+
+    fn scalar_projection(&self, vb: &[F]) -> F {
+        self.vecdot(vb) / vb.vec2norm()
+    }
+
+    fn vector_projection(&self, vb: &[F]) -> Vec<F> {
+        let mut p = vb.to_vec();
+        p.vecscale(self.vecdot(vb) / vb.vecdot(vb));
+        p
+    }
+
+    fn vector_rejection(&self, vb: &[F]) -> Vec<F> {
+        let mut r = self.to_vec();
+        r.vecadd(vb, -self.vecdot(vb) / vb.vecdot(vb));
+        r
+    }
+
+    fn angle_between(&self, vb: &[F]) -> F {
+        // atan2 keeps full precision for nearly (anti)parallel vectors,
+        // unlike acos of the cosine
+        let sin = self.vector_rejection(vb).vec2norm() * vb.vec2norm();
+        sin.atan2(self.vecdot(vb))
+    }
+
+    fn checked_cosine_similarity(&self, vb: &[F], tol: F) -> Option<F> {
+        let (na, nb) = (self.vec2norm(), vb.vec2norm());
+        if na <= tol || nb <= tol {
+            return None;
+        }
+        Some((self.vecdot(vb) / (na * nb)).max(-F::one()).min(F::one()))
+    }
+
+    fn checked_scalar_projection(&self, vb: &[F], tol: F) -> Option<F> {
+        let nb = vb.vec2norm();
+        if nb <= tol {
+            return None;
+        }
+        Some(self.vecdot(vb) / nb)
+    }
+
+    fn checked_vector_projection(&self, vb: &[F], tol: F) -> Option<Vec<F>> {
+        let nb = vb.vec2norm();
+        if nb <= tol {
+            return None;
+        }
+        let mut p = vb.to_vec();
+        p.vecscale(self.vecdot(vb) / (nb * nb));
+        Some(p)
+    }
+
+    fn checked_vector_rejection(&self, vb: &[F], tol: F) -> Option<Vec<F>> {
+        let nb = vb.vec2norm();
+        if nb <= tol {
+            return None;
+        }
+        let mut r = self.to_vec();
+        r.vecadd(vb, -self.vecdot(vb) / (nb * nb));
+        Some(r)
+    }
+
+    fn checked_angle_between(&self, vb: &[F], tol: F) -> Option<F> {
+        if self.vec2norm() <= tol || vb.vec2norm() <= tol {
+            return None;
+        }
+        Some(self.angle_between(vb))
+    }
+}
+
+impl<const N: usize> VectorProject for [f64; N] {
+    /// The cosine of two non-zero vectors `va` and `vb`
+    fn cosine_similarity(&self, vb: &Self) -> f64 {
+        self[..].cosine_similarity(vb)
+    }
+
+    /// the scalar projection of a vector a onto a vector b
+    fn scalar_projection(&self, vb: &Self) -> f64 {
+        self[..].scalar_projection(vb)
+    }
+
+    /// The vector component of `va` parallel to `vb`
+    fn vector_projection(&self, vb: &Self) -> Self {
+        let mut p = *vb;
+        p.vecscale(self.vecdot(vb) / vb.vecdot(vb));
+        p
+    }
+
+    /// The vector component of `va` perpendicular to `vb`
+    fn vector_rejection(&self, vb: &Self) -> Self {
+        let mut r = *self;
+        r.vecadd(vb, -self.vecdot(vb) / vb.vecdot(vb));
+        r
+    }
+
+    fn angle_between(&self, vb: &Self) -> f64 {
+        self[..].angle_between(vb)
+    }
+
+    fn checked_cosine_similarity(&self, vb: &Self, tol: f64) -> Option<f64> {
+        self[..].checked_cosine_similarity(vb, tol)
+    }
+
+    fn checked_scalar_projection(&self, vb: &Self, tol: f64) -> Option<f64> {
+        self[..].checked_scalar_projection(vb, tol)
+    }
+
+    fn checked_vector_projection(&self, vb: &Self, tol: f64) -> Option<Self> {
+        let nb = vb.vec2norm();
+        if nb <= tol {
+            return None;
+        }
+        let mut p = *vb;
+        p.vecscale(self.vecdot(vb) / (nb * nb));
+        Some(p)
+    }
+
+    fn checked_vector_rejection(&self, vb: &Self, tol: f64) -> Option<Self> {
+        let p = self.checked_vector_projection(vb, tol)?;
+        let mut r = *self;
+        r.vecadd(&p, -1.0);
+        Some(r)
+    }
+}
+// 7a3e5f10 ends here
+
+// [[file:../vecfx.note::5c2e8f93][5c2e8f93]]
+/// A linear subspace given by an orthonormal basis, for projecting vectors
+/// onto it or out of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Subspace {
+    basis: Vec<Vec<f64>>,
+    // the dimension of the ambient space
+    n: usize,
+}
+
+impl Subspace {
+    /// Create the subspace spanned by `vectors`, which need not be
+    /// orthogonal. Vectors linearly dependent on the previous ones within
+    /// `tol` (see [`crate::orthonormalize`]) are ignored.
+    ///
+    /// The ambient dimension is the length of the vectors, or 0 if there are
+    /// none. Use [`Subspace::empty`] for no constraints in a known dimension.
+    pub fn new<V: AsRef<[f64]>>(vectors: &[V], tol: f64) -> Self {
+        let n = vectors.first().map_or(0, |v| v.as_ref().len());
+        let basis = crate::orthonormalize(vectors, crate::GramSchmidt::ModifiedReorthogonalized, tol).basis;
+        Self { basis, n }
+    }
+
+    /// The zero-dimensional subspace of an `n`-dimensional space.
+    pub fn empty(n: usize) -> Self {
+        Self { basis: vec![], n }
+    }
+
+    /// The orthonormal basis of the subspace.
+    pub fn basis(&self) -> &[Vec<f64>] {
+        &self.basis
+    }
+
+    /// The dimension of the subspace.
+    pub fn dim(&self) -> usize {
+        self.basis.len()
+    }
+
+    /// The dimension of the space the subspace lives in.
+    pub fn ambient_dim(&self) -> usize {
+        self.n
+    }
+
+    /// The component of `v` within the subspace, P v.
+    pub fn project(&self, v: &[f64]) -> Vec<f64> {
+        let mut p = vec![0.0; v.len()];
+        for q in &self.basis {
+            p.vecadd(q, q.vecdot(v));
+        }
+        p
+    }
+
+    /// The component of `v` perpendicular to the subspace, (I - P) v.
+    pub fn reject(&self, v: &[f64]) -> Vec<f64> {
+        let mut r = v.to_vec();
+        self.reject_in_place(&mut r);
+        r
+    }
+
+    /// Remove the components within the subspace from `v` in place.
+    pub fn reject_in_place(&self, v: &mut [f64]) {
+        for q in &self.basis {
+            let c = q.vecdot(v);
+            v.vecadd(q, -c);
+        }
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl Subspace {
+    /// The projector P = Q Q^T onto the subspace, as an n x n matrix. It is
+    /// the zero matrix for an empty subspace.
+    pub fn projector(&self) -> na::DMatrix<f64> {
+        let flat = self.basis.concat();
+        let q = na::DMatrix::from_column_slice(self.n, self.dim(), &flat);
+        &q * q.transpose()
+    }
+
+    /// The complementary projector I - P. It is the identity for an empty
+    /// subspace.
+    pub fn complement_projector(&self) -> na::DMatrix<f64> {
+        na::DMatrix::identity(self.n, self.n) - self.projector()
+    }
+
+    /// Project the subspace out of a symmetric matrix such as a Hessian:
+    /// (I - P) H (I - P).
+    pub fn reject_matrix(&self, h: &na::DMatrix<f64>) -> na::DMatrix<f64> {
+        if self.basis.is_empty() {
+            return h.clone();
+        }
+        let c = self.complement_projector();
+        &c * h * &c
+    }
+}
+
+/// The component of `v` within the subspace spanned by `vectors`. See
+/// [`Subspace::new`] for the meaning of `tol`.
+pub fn subspace_projection<V: AsRef<[f64]>>(v: &[f64], vectors: &[V], tol: f64) -> Vec<f64> {
+    Subspace::new(vectors, tol).project(v)
+}
+
+/// The component of `v` perpendicular to the subspace spanned by `vectors`.
+/// See [`Subspace::new`] for the meaning of `tol`.
+pub fn subspace_rejection<V: AsRef<[f64]>>(v: &[f64], vectors: &[V], tol: f64) -> Vec<f64> {
+    Subspace::new(vectors, tol).reject(v)
+}
+// 5c2e8f93 ends here
+
 // [[file:../vecfx.note::f82eabd3][f82eabd3]]
+#[cfg(feature = "nalgebra")]
 #[test]
 fn test_vector_projection() {
+    use crate::VecFloatExt;
     use approx::*;
 
     let va = [1.0; 3].to_vector();
@@ -72,5 +411,130 @@ fn test_vector_projection() {
     for i in 0..3 {
         assert_relative_eq!(x[i], e[i], epsilon = 1e-4);
     }
+
+    let x = va.angle_between(&vb);
+    assert_relative_eq!(x, 0.9976931918526477f64.acos(), epsilon = 1e-6);
+}
+
+#[test]
+fn test_vector_projection_slice() {
+    use approx::*;
+
+    let va = [1.0; 3];
+    let vb = [1.1, 1.2, 1.3];
+
+    let x = va.cosine_similarity(&vb);
+    assert_relative_eq!(x, 0.9976931918526477, epsilon = 1e-4);
+    assert_relative_eq!(va[..].cosine_similarity(&vb), x);
+
+    let x = va.scalar_projection(&vb);
+    assert_relative_eq!(x, 1.728055, epsilon = 1e-4);
+    assert_relative_eq!(va[..].scalar_projection(&vb), x);
+
+    let e = [0.9124424, 0.99539171, 1.07834101];
+    let x = va.vector_projection(&vb);
+    let y = va[..].vector_projection(&vb);
+    for i in 0..3 {
+        assert_relative_eq!(x[i], e[i], epsilon = 1e-4);
+        assert_relative_eq!(y[i], e[i], epsilon = 1e-4);
+    }
+
+    let e = [0.0875576, 0.00460829, -0.07834101];
+    let x = va.vector_rejection(&vb);
+    let y = va[..].vector_rejection(&vb);
+    for i in 0..3 {
+        assert_relative_eq!(x[i], e[i], epsilon = 1e-4);
+        assert_relative_eq!(y[i], e[i], epsilon = 1e-4);
+    }
+
+    let x = [1.0, 0.0].angle_between(&[0.0, 2.0]);
+    assert_relative_eq!(x, std::f64::consts::FRAC_PI_2);
+    let x = [1.0, 1.0][..].angle_between(&[-2.0, -2.0]);
+    assert_relative_eq!(x, std::f64::consts::PI);
+}
+
+#[test]
+fn test_vector_projection_checked() {
+    use approx::*;
+
+    let zero = [0.0; 3];
+    let va = [1.0, 2.0, 3.0];
+    let tol = 1e-8;
+    assert!(zero.cosine_similarity(&va).is_nan());
+    assert_eq!(zero.checked_cosine_similarity(&va, tol), None);
+    assert_eq!(va.checked_cosine_similarity(&zero, tol), None);
+    assert_eq!(va.checked_vector_projection(&zero, tol), None);
+    assert_eq!(va.checked_vector_rejection(&[1e-10, 0.0, 0.0], tol), None);
+    assert_eq!(va[..].checked_scalar_projection(&zero, tol), None);
+    assert_eq!(va[..].checked_angle_between(&zero, tol), None);
+
+    // projecting a vanishing force is fine
+    assert_eq!(zero.checked_vector_projection(&va, tol), Some(zero));
+    assert_eq!(zero[..].checked_vector_rejection(&va, tol), Some(zero.to_vec()));
+
+    // nearly parallel inputs
+    let vb = [2.0, 4.0, 6.0 + 1e-12];
+    let c = va.checked_cosine_similarity(&vb, tol).unwrap();
+    assert!(c <= 1.0);
+    assert_relative_eq!(c, 1.0);
+    let r = va.checked_vector_rejection(&vb, tol).unwrap();
+    assert!(r.vec2norm() < 1e-10);
+
+    #[cfg(feature = "nalgebra")]
+    {
+        let va = va.to_vector();
+        let vb = vb.to_vector();
+        let zero = zero.to_vector();
+        assert_eq!(va.checked_cosine_similarity(&zero, tol), None);
+        assert_eq!(va.checked_vector_projection(&zero, tol), None);
+        let p = va.checked_vector_projection(&vb, tol).unwrap();
+        assert_relative_eq!(p, va.vector_projection(&vb), epsilon = 1e-8);
+        let r = zero.checked_vector_rejection(&va, tol).unwrap();
+        assert_eq!(r.norm(), 0.0);
+    }
+}
+
+#[test]
+fn test_subspace_projection() {
+    use approx::*;
+
+    // two non-orthogonal constraint directions in the xy plane, plus a
+    // redundant one
+    let constraints = [[1.0, 1.0, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0], [2.0, 1.0, 0.0, 0.0]];
+    let g = [1.0, 2.0, 3.0, 4.0];
+    let sub = Subspace::new(&constraints, 1e-8);
+    assert_eq!(sub.dim(), 2);
+    let p = sub.project(&g);
+    let r = sub.reject(&g);
+    for i in 0..4 {
+        assert_relative_eq!(p[i], [1.0, 2.0, 0.0, 0.0][i], epsilon = 1e-12);
+        assert_relative_eq!(r[i], [0.0, 0.0, 3.0, 4.0][i], epsilon = 1e-12);
+    }
+    assert_eq!(subspace_rejection(&g, &constraints, 1e-8), r);
+    assert_eq!(subspace_projection(&g, &constraints, 1e-8), p);
+
+    #[cfg(feature = "nalgebra")]
+    {
+        let pm = sub.projector();
+        let cm = sub.complement_projector();
+        let gv = g.to_vector();
+        assert_relative_eq!(&pm * &gv, p.to_vector(), epsilon = 1e-12);
+        assert_relative_eq!(&cm * &gv, r.to_vector(), epsilon = 1e-12);
+        assert_relative_eq!(&pm * &pm, pm.clone(), epsilon = 1e-12);
+
+        let h = na::DMatrix::from_fn(4, 4, |i, j| 1.0 / (1 + i + j) as f64);
+        let hp = sub.reject_matrix(&h);
+        assert_relative_eq!(&hp * [1.0, 0.0, 0.0, 0.0].to_vector(), na::DVector::zeros(4), epsilon = 1e-12);
+        assert_relative_eq!(hp[(2, 3)], h[(2, 3)], epsilon = 1e-12);
+
+        // no constraints
+        let free = Subspace::empty(4);
+        assert_eq!(free.projector(), na::DMatrix::zeros(4, 4));
+        assert_eq!(free.complement_projector(), na::DMatrix::identity(4, 4));
+        let zero = Subspace::new(&[[0.0; 4]], 1e-8);
+        assert_eq!(zero.dim(), 0);
+        assert_eq!(zero.ambient_dim(), 4);
+        assert_eq!(zero.complement_projector(), na::DMatrix::identity(4, 4));
+    }
 }
 // f82eabd3 ends here
@@ -2,6 +2,9 @@
 // abstracted from: https://github.com/rust-lang/libtest/blob/master/libtest/stats.rs
 
 // [[file:~/Workspace/Programming/gchemol-rs/vecfx/vecfx.note::*stats][stats:1]]
+#[cfg(feature = "rayon")]
+use crate::kernel::par;
+
 /// Trait that provides simple descriptive statistics on a univariate set of numeric samples.
 pub trait StatsExt {
     /// Sum of the samples.
@@ -49,49 +52,81 @@ pub trait StatsExt {
     fn imax(&self) -> usize;
 }
 
+/// Add `xs` to the list of non-overlapping partial sums, keeping their total
+/// exact (Shewchuk's algorithm).
+fn msum_partials(xs: impl Iterator<Item = f64>, partials: &mut Vec<f64>) {
+    for x in xs {
+        let mut x = x;
+        let mut j = 0;
+        // This inner loop applies `hi`/`lo` summation to each
+        // partial so that the list of partial sums remains exact.
+        for i in 0..partials.len() {
+            let mut y: f64 = partials[i];
+            if x.abs() < y.abs() {
+                std::mem::swap(&mut x, &mut y);
+            }
+            // Rounded `x+y` is stored in `hi` with round-off stored in
+            // `lo`. Together `hi+lo` are exactly equal to `x+y`.
+            let hi = x + y;
+            let lo = y - (hi - x);
+            if lo != 0.0 {
+                partials[j] = lo;
+                j += 1;
+            }
+            x = hi;
+        }
+        if j >= partials.len() {
+            partials.push(x);
+        } else {
+            partials[j] = x;
+            partials.truncate(j + 1);
+        }
+    }
+}
+
 impl StatsExt for [f64] {
     // FIXME #11059 handle NaN, inf and overflow
     fn sum(&self) -> f64 {
         let mut partials = vec![];
 
-        for &x in self {
-            let mut x = x;
-            let mut j = 0;
-            // This inner loop applies `hi`/`lo` summation to each
-            // partial so that the list of partial sums remains exact.
-            for i in 0..partials.len() {
-                let mut y: f64 = partials[i];
-                if x.abs() < y.abs() {
-                    std::mem::swap(&mut x, &mut y);
-                }
-                // Rounded `x+y` is stored in `hi` with round-off stored in
-                // `lo`. Together `hi+lo` are exactly equal to `x+y`.
-                let hi = x + y;
-                let lo = y - (hi - x);
-                if lo != 0.0 {
-                    partials[j] = lo;
-                    j += 1;
-                }
-                x = hi;
-            }
-            if j >= partials.len() {
-                partials.push(x);
-            } else {
-                partials[j] = x;
-                partials.truncate(j + 1);
-            }
+        #[cfg(feature = "rayon")]
+        if self.len() >= par::THRESHOLD {
+            // the partials of each chunk are exact, so merging them in chunk
+            // order gives the same exact total as the serial loop
+            let chunks = par::map_chunks(self, |c| {
+                let mut partials = vec![];
+                msum_partials(c.iter().copied(), &mut partials);
+                partials
+            });
+            msum_partials(chunks.into_iter().flatten(), &mut partials);
+        } else {
+            msum_partials(self.iter().copied(), &mut partials);
         }
+
+        #[cfg(not(feature = "rayon"))]
+        msum_partials(self.iter().copied(), &mut partials);
+
         let zero: f64 = 0.0;
         partials.iter().fold(zero, |p, q| p + *q)
     }
 
     fn min(&self) -> f64 {
         assert!(!self.is_empty());
+        #[cfg(feature = "rayon")]
+        if self.len() >= par::THRESHOLD {
+            let chunks = par::map_chunks(self, |c| c.iter().fold(c[0], |p, q| p.min(*q)));
+            return chunks.iter().fold(chunks[0], |p, q| p.min(*q));
+        }
         self.iter().fold(self[0], |p, q| p.min(*q))
     }
 
     fn max(&self) -> f64 {
         assert!(!self.is_empty());
+        #[cfg(feature = "rayon")]
+        if self.len() >= par::THRESHOLD {
+            let chunks = par::map_chunks(self, |c| c.iter().fold(c[0], |p, q| p.max(*q)));
+            return chunks.iter().fold(chunks[0], |p, q| p.max(*q));
+        }
         self.iter().fold(self[0], |p, q| p.max(*q))
     }
 
@@ -105,11 +140,22 @@ impl StatsExt for [f64] {
             0.0
         } else {
             let mean = self.mean();
-            let mut v: f64 = 0.0;
-            for s in self {
-                let x = *s - mean;
-                v += x * x;
-            }
+            let sum_squares = |xs: &[f64]| {
+                let mut v: f64 = 0.0;
+                for s in xs {
+                    let x = *s - mean;
+                    v += x * x;
+                }
+                v
+            };
+            #[cfg(feature = "rayon")]
+            let v: f64 = if self.len() >= par::THRESHOLD {
+                par::map_chunks(self, sum_squares).iter().sum()
+            } else {
+                sum_squares(self)
+            };
+            #[cfg(not(feature = "rayon"))]
+            let v = sum_squares(self);
             // N.B., this is _supposed to be_ len-1, not len. If you
             // change it back to len, you will be calculating a
             // population variance, not a sample variance.
@@ -144,7 +190,7 @@ mod tests {
 
     #[test]
     fn test_stats_nan() {
-        let xs = &[1.0, 2.0, std::f64::NAN, 3.0, 4.0];
+        let xs = &[1.0, 2.0, f64::NAN, 3.0, 4.0];
         assert_eq!(xs.min(), 1.0);
         assert_eq!(xs.max(), 4.0);
         assert_eq!(xs.imin(), 0);
@@ -165,5 +211,22 @@ mod tests {
         assert_eq!(val.imin(), 1);
         assert_eq!(val.imax(), 0);
     }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_stats_par() {
+        let n = par::THRESHOLD * 2 + 5;
+        let xs: Vec<f64> = (0..n).map(|i| ((i * 7919) % 113) as f64 / 17.0 - 3.0).collect();
+        let mut partials = vec![];
+        msum_partials(xs.iter().copied(), &mut partials);
+        let sum: f64 = partials.iter().sum();
+        assert_eq!(xs.sum(), sum);
+        assert_eq!(xs.min(), xs.iter().fold(xs[0], |p, q| p.min(*q)));
+        assert_eq!(xs.max(), xs.iter().fold(xs[0], |p, q| p.max(*q)));
+        let pool = rayon::ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+        let var = pool.install(|| xs.var());
+        assert_eq!(var.to_bits(), xs.var().to_bits());
+        assert_relative_eq!(var, xs.std_dev().powi(2), max_relative = 1e-12);
+    }
 }
 // test:1 ends here
@@ -0,0 +1,677 @@
+// [[file:../vecfx.note::4f9a2c07][4f9a2c07]]
+use crate::eigen3::jacobi_eigen;
+use crate::{linear_assignment, principal_axes, PrincipalAxes, RotorType};
+use crate::{Array3MathExt, ArrayMathExt, Mat3, Mat3Ext, VecFloatNDExt};
+
+#[cfg(feature = "nalgebra")]
+use nalgebra as na;
+// 4f9a2c07 ends here
+
+// [[file:../vecfx.note::b1e63d58][b1e63d58]]
+/// The optimal superposition of a mobile point set onto a reference.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Superposition {
+    /// Root mean square deviation after superposition.
+    pub rmsd: f64,
+    /// The rotation, applied first.
+    pub rotation: Mat3,
+    /// The translation, applied after the rotation.
+    pub translation: [f64; 3],
+}
+
+impl Superposition {
+    /// Transform `positions` in place: x -> R x + t.
+    pub fn apply(&self, positions: &mut [[f64; 3]]) {
+        self.rotation.apply_to(positions);
+        positions.translate(self.translation);
+    }
+}
+
+/// Return the (weighted) RMSD between two point sets as they are, without
+/// superposition.
+///
+/// # Panics
+///
+/// * if the point sets or `weights` differ in length.
+pub fn rmsd(reference: &[[f64; 3]], mobile: &[[f64; 3]], weights: Option<&[f64]>) -> f64 {
+    assert_eq!(reference.len(), mobile.len(), "point sets differ in size");
+    let mut sum = 0.0;
+    let mut wsum = 0.0;
+    for (i, (a, b)) in reference.iter().zip(mobile).enumerate() {
+        let w = weights.map_or(1.0, |w| w[i]);
+        sum += w * a.array_sub(*b).array_norm_squared();
+        wsum += w;
+    }
+    (sum / wsum).sqrt()
+}
+
+/// Weighted centers of both sets, the inner product matrix M = sum(w x y^T)
+/// of centered mobile x and reference y, and (G_x + G_y) / 2 and the total
+/// weight.
+fn inner_product(
+    reference: &[[f64; 3]],
+    mobile: &[[f64; 3]],
+    weights: Option<&[f64]>,
+) -> ([[f64; 3]; 2], Mat3, f64, f64) {
+    assert_eq!(reference.len(), mobile.len(), "point sets differ in size");
+    let (cr, cm) = match weights {
+        Some(w) => (reference.center_of_mass(w), mobile.center_of_mass(w)),
+        None => (reference.centroid(), mobile.centroid()),
+    };
+    let mut m = [[0.0; 3]; 3];
+    let mut e0 = 0.0;
+    let mut wsum = 0.0;
+    for (i, (r, p)) in reference.iter().zip(mobile).enumerate() {
+        let w = weights.map_or(1.0, |w| w[i]);
+        let y = r.array_sub(cr);
+        let x = p.array_sub(cm);
+        for (row, xi) in m.iter_mut().zip(x) {
+            *row = row.array_add(y.array_scale(w * xi));
+        }
+        e0 += 0.5 * w * (x.array_norm_squared() + y.array_norm_squared());
+        wsum += w;
+    }
+    ([cr, cm], m, e0, wsum)
+}
+
+/// Translation moving the rotated mobile center onto the reference center.
+/// A slightly negative `msd` from rounding is clamped to zero, while NaN is
+/// kept so that invalid input never looks like a perfect match.
+fn superposition(rotation: Mat3, centers: [[f64; 3]; 2], msd: f64) -> Superposition {
+    let [cr, cm] = centers;
+    Superposition {
+        rmsd: if msd < 0.0 { 0.0 } else { msd.sqrt() },
+        rotation,
+        translation: cr.array_sub(rotation.matvec(cm)),
+    }
+}
+
+/// Superpose `mobile` onto `reference` with minimal (weighted) RMSD, using
+/// the quaternion characteristic polynomial (QCP) method of Theobald (Acta
+/// Cryst. A 2005, 61, 478). No dependency on nalgebra.
+///
+/// # Panics
+///
+/// * if the point sets or `weights` differ in length.
+///
+/// # Example
+/// ```
+/// use vecfx::*;
+///
+/// let reference = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 2.0, 0.0]];
+/// let mut mobile = [[5.0, 0.0, 0.0], [5.0, 1.0, 0.0], [3.0, 0.0, 0.0]];
+/// let sp = superpose_qcp(&reference, &mobile, None);
+/// assert!(sp.rmsd < 1e-8);
+/// sp.apply(&mut mobile);
+/// assert!(rmsd(&reference, &mobile, None) < 1e-8);
+/// ```
+pub fn superpose_qcp(reference: &[[f64; 3]], mobile: &[[f64; 3]], weights: Option<&[f64]>) -> Superposition {
+    let (centers, m, e0, wsum) = inner_product(reference, mobile, weights);
+    let [[sxx, sxy, sxz], [syx, syy, syz], [szx, szy, szz]] = m;
+    #[rustfmt::skip]
+    let k = [
+        [sxx + syy + szz, syz - szy, szx - sxz, sxy - syx],
+        [syz - szy, sxx - syy - szz, sxy + syx, szx + sxz],
+        [szx - sxz, sxy + syx, -sxx + syy - szz, syz + szy],
+        [sxy - syx, szx + sxz, syz + szy, -sxx - syy + szz],
+    ];
+
+    // largest root of the characteristic polynomial of K by Newton's
+    // method, starting from its upper bound E0
+    let c2 = -2.0 * m.iter().map(|r| r.array_norm_squared()).sum::<f64>();
+    let c1 = -8.0 * m.det();
+    let c0 = det4(&k);
+    let mut lambda = e0;
+    for _ in 0..50 {
+        let p = ((lambda * lambda + c2) * lambda + c1) * lambda + c0;
+        let dp = (4.0 * lambda * lambda + 2.0 * c2) * lambda + c1;
+        if dp == 0.0 {
+            break;
+        }
+        let delta = p / dp;
+        lambda -= delta;
+        if delta.abs() <= 1e-14 * lambda.abs() {
+            break;
+        }
+    }
+
+    let q = max_eigenvector4(&k, lambda);
+    superposition(quaternion_rotation(q), centers, 2.0 * (e0 - lambda) / wsum)
+}
+
+/// Eigenvector of symmetric `k` for its eigenvalue `lambda`, from the
+/// largest column of the adjugate of K - lambda I. Falls back to a full
+/// diagonalization when the eigenvalue is degenerate, as for collinear
+/// points.
+fn max_eigenvector4(k: &[[f64; 4]; 4], lambda: f64) -> [f64; 4] {
+    let mut a = *k;
+    for (i, row) in a.iter_mut().enumerate() {
+        row[i] -= lambda;
+    }
+    let adj: [[f64; 4]; 4] = std::array::from_fn(|i| std::array::from_fn(|j| cofactor4(&a, i, j)));
+    let col = adj
+        .into_iter()
+        .max_by(|x, y| x.array_norm_squared().total_cmp(&y.array_norm_squared()))
+        .unwrap();
+    let scale = k.iter().fold(0.0, |s: f64, r| s.max(r.array_norm()));
+    let norm = col.array_norm();
+    if norm > 1e-6 * scale.powi(3) && norm.is_finite() {
+        col.array_scale(norm.recip())
+    } else {
+        let (values, vectors) = jacobi_eigen(k);
+        let imax = (0..4).max_by(|&i, &j| values[i].total_cmp(&values[j])).unwrap();
+        vectors[imax]
+    }
+}
+
+fn cofactor4(a: &[[f64; 4]; 4], i: usize, j: usize) -> f64 {
+    let rows = (0..4).filter(|&r| r != i);
+    let minor: Vec<[f64; 3]> = rows
+        .map(|r| {
+            let mut cols = (0..4).filter(|&c| c != j).map(|c| a[r][c]);
+            std::array::from_fn(|_| cols.next().unwrap())
+        })
+        .collect();
+    let minor: Mat3 = [minor[0], minor[1], minor[2]];
+    let sign = if (i + j).is_multiple_of(2) { 1.0 } else { -1.0 };
+    sign * minor.det()
+}
+
+fn det4(a: &[[f64; 4]; 4]) -> f64 {
+    (0..4).map(|j| a[0][j] * cofactor4(a, 0, j)).sum()
+}
+
+/// Rotation matrix of the unit quaternion (q0, q1, q2, q3), q0 being the
+/// scalar part.
+fn quaternion_rotation(q: [f64; 4]) -> Mat3 {
+    let [a, b, c, d] = q;
+    [
+        [
+            a * a + b * b - c * c - d * d,
+            2.0 * (b * c - a * d),
+            2.0 * (b * d + a * c),
+        ],
+        [
+            2.0 * (b * c + a * d),
+            a * a - b * b + c * c - d * d,
+            2.0 * (c * d - a * b),
+        ],
+        [
+            2.0 * (b * d - a * c),
+            2.0 * (c * d + a * b),
+            a * a - b * b - c * c + d * d,
+        ],
+    ]
+}
+
+#[cfg(feature = "nalgebra")]
+/// Superpose `mobile` onto `reference` with minimal (weighted) RMSD, using
+/// the Kabsch algorithm with the SVD of nalgebra.
+///
+/// # Panics
+///
+/// * if the point sets or `weights` differ in length.
+pub fn superpose_kabsch(reference: &[[f64; 3]], mobile: &[[f64; 3]], weights: Option<&[f64]>) -> Superposition {
+    let (centers, m, e0, wsum) = inner_product(reference, mobile, weights);
+    // H = sum(w x y^T) = U S V^T, R = V diag(1, 1, d) U^T. `svd` sorts the
+    // singular values in descending order, so a reflection is fixed by
+    // flipping the last, smallest one.
+    let h = na::Matrix3::from_fn(|i, j| m[i][j]);
+    let svd = h.svd(true, true);
+    let (u, v_t) = (svd.u.unwrap(), svd.v_t.unwrap());
+    let d = (v_t.transpose() * u.transpose()).determinant().signum();
+    let s = svd.singular_values;
+    let r = v_t.transpose() * na::Matrix3::from_diagonal(&na::Vector3::new(1.0, 1.0, d)) * u.transpose();
+    let rotation = std::array::from_fn(|i| std::array::from_fn(|j| r[(i, j)]));
+    let trace = s[0] + s[1] + d * s[2];
+    superposition(rotation, centers, 2.0 * (e0 - trace) / wsum)
+}
+
+/// Superpose `mobile` onto `reference` in place with [`superpose_qcp`], and
+/// return the superposition applied.
+pub fn align(reference: &[[f64; 3]], mobile: &mut [[f64; 3]], weights: Option<&[f64]>) -> Superposition {
+    let sp = superpose_qcp(reference, mobile, weights);
+    sp.apply(mobile);
+    sp
+}
+
+// b1e63d58 ends here
+
+// [[file:../vecfx.note::71b4c2e8][71b4c2e8]]
+/// Superposition of a mobile point set onto a reference after matching
+/// equivalent points, as found by [`superpose_permuted`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PermutedSuperposition {
+    /// The superposition of the reordered mobile points.
+    pub superposition: Superposition,
+    /// `permutation[i]` is the index of the mobile point matched to
+    /// reference point `i`.
+    pub permutation: Vec<usize>,
+}
+
+impl PermutedSuperposition {
+    /// Return the mobile points reordered like the reference and
+    /// superposed onto it.
+    pub fn apply(&self, mobile: &[[f64; 3]]) -> Vec<[f64; 3]> {
+        let mut positions: Vec<_> = self.permutation.iter().map(|&k| mobile[k]).collect();
+        self.superposition.apply(&mut positions);
+        positions
+    }
+}
+
+/// Superpose `mobile` onto `reference` with minimal RMSD over rotations,
+/// translations and permutations of equivalent points, which share the
+/// same class label (e.g. element). Returns `None` if the two sets do not
+/// have the same number of points in each class, or if any coordinate is
+/// not finite.
+///
+/// Starting from the given order within each class and from the four
+/// orientations of the principal axes, points are matched within each class
+/// by the Hungarian algorithm and the matched sets are superposed with
+/// [`superpose_qcp`], until the matching does not change or `max_iter`
+/// rounds are done. The best result is returned. `weights` are indexed like
+/// the reference points, and equivalent points should have the same weight.
+///
+/// Principal axes of degenerate moments are arbitrary, so for symmetric and
+/// spherical tops the frames spanned by the center and two anchor points
+/// are also tried, pairing the reference anchors with up to 16 equivalent
+/// pairs of mobile points at matching distances. The search stops early
+/// once a start matches exactly.
+///
+/// Each start costs up to `max_iter` assignments of O(m^3) for a class of
+/// m points, and there are at most 21 starts. Like any local search, the
+/// result is not guaranteed to be the global minimum.
+///
+/// # Panics
+///
+/// * if labels, points or `weights` differ in length.
+///
+/// # Example
+/// ```
+/// use vecfx::*;
+///
+/// let reference = [[0.0, 0.0, 0.1173], [0.0, 0.7572, -0.4692], [0.0, -0.7572, -0.4692]];
+/// // the same water with hydrogens swapped and moved
+/// let mobile = [[1.0, -0.7572, -0.4692], [1.0, 0.0, 0.1173], [1.0, 0.7572, -0.4692]];
+/// let sp = superpose_permuted(&reference, &mobile, &["O", "H", "H"], &["H", "O", "H"], None, 10).unwrap();
+/// assert!(sp.superposition.rmsd < 1e-6);
+/// // the oxygen is matched; by symmetry the hydrogens may go either way
+/// assert_eq!(sp.permutation[0], 1);
+/// ```
+pub fn superpose_permuted<L: PartialEq>(
+    reference: &[[f64; 3]],
+    mobile: &[[f64; 3]],
+    reference_labels: &[L],
+    mobile_labels: &[L],
+    weights: Option<&[f64]>,
+    max_iter: usize,
+) -> Option<PermutedSuperposition> {
+    assert_eq!(reference.len(), mobile.len(), "point sets differ in size");
+    assert_eq!(
+        reference.len(),
+        reference_labels.len(),
+        "labels and points differ in size"
+    );
+    assert_eq!(mobile.len(), mobile_labels.len(), "labels and points differ in size");
+
+    // indices of reference and mobile points in each class
+    let mut classes: Vec<(&L, Vec<usize>, Vec<usize>)> = vec![];
+    for (i, l) in reference_labels.iter().enumerate() {
+        match classes.iter_mut().find(|c| c.0 == l) {
+            Some(c) => c.1.push(i),
+            None => classes.push((l, vec![i], vec![])),
+        }
+    }
+    for (i, l) in mobile_labels.iter().enumerate() {
+        classes.iter_mut().find(|c| c.0 == l)?.2.push(i);
+    }
+    if classes.iter().any(|c| c.1.len() != c.2.len()) {
+        return None;
+    }
+    if reference.iter().chain(mobile).flatten().any(|x| !x.is_finite()) {
+        return None;
+    }
+
+    // match each reference point to the nearest equivalent mobile point,
+    // after transforming the mobile points with `sp`
+    let matching = |sp: &Superposition| -> Option<Vec<usize>> {
+        let mut moved = mobile.to_vec();
+        sp.apply(&mut moved);
+        let mut permutation = vec![0; reference.len()];
+        for (_, ir, im) in &classes {
+            let n = ir.len();
+            let cost: Vec<f64> = ir
+                .iter()
+                .flat_map(|&i| im.iter().map(move |&j| (i, j)))
+                .map(|(i, j)| reference[i].array_sub(moved[j]).array_norm_squared())
+                .collect();
+            for (k, l) in linear_assignment(&cost, n)?.into_iter().enumerate() {
+                permutation[ir[k]] = im[l];
+            }
+        }
+        Some(permutation)
+    };
+    let fit = |permutation: &[usize]| {
+        let permuted: Vec<_> = permutation.iter().map(|&k| mobile[k]).collect();
+        superpose_qcp(reference, &permuted, weights)
+    };
+
+    // initial guesses: the given order of the points within each class, and
+    // the principal axes of the mobile points mapped onto those of the
+    // reference in the four right-handed ways
+    let mut permutation = vec![0; reference.len()];
+    for (_, ir, im) in &classes {
+        for (&i, &j) in ir.iter().zip(im) {
+            permutation[i] = j;
+        }
+    }
+    let mut guesses = vec![fit(&permutation)];
+    let unit = vec![1.0; reference.len()];
+    let (wr, wm) = match weights {
+        Some(w) => {
+            // equivalent points share the weight
+            let wm: Vec<_> = (0..mobile.len())
+                .map(|j| w[classes.iter().find(|c| c.2.contains(&j)).unwrap().1[0]])
+                .collect();
+            (w.to_vec(), wm)
+        }
+        None => (unit.clone(), unit),
+    };
+    let pr = principal_axes(reference, &wr);
+    let pm = principal_axes(mobile, &wm);
+    let centers = [pr.center, pm.center];
+    for signs in [[1.0, 1.0, 1.0], [1.0, -1.0, -1.0], [-1.0, 1.0, -1.0], [-1.0, -1.0, 1.0]] {
+        let axes = std::array::from_fn(|i| pm.axes[i].array_scale(signs[i]));
+        guesses.push(superposition(pr.axes.transpose().matmul(&axes), centers, f64::INFINITY));
+    }
+
+    // the axes of degenerate moments are arbitrary, so for symmetric and
+    // spherical tops also map the frame spanned by two anchor points of the
+    // reference onto the frames of equivalent mobile point pairs. Only the
+    // mobile points closest in distances to the center and to each other
+    // are tried, which are invariant under rotation.
+    let degenerate = |p: &PrincipalAxes| {
+        matches!(
+            p.rotor_type(DEGENERATE_MOMENTS),
+            RotorType::SphericalTop | RotorType::ProlateSymmetricTop | RotorType::OblateSymmetricTop
+        )
+    };
+    if degenerate(&pr) || degenerate(&pm) {
+        if let Some((a, b)) = frame_anchors(reference, pr.center, &classes) {
+            // the anchors are chosen off the center and off each other's line
+            let fr = frame(pr.center, reference[a], reference[b]).unwrap();
+            let class_of = |i: usize| &classes.iter().find(|c| c.1.contains(&i)).unwrap().2;
+            let ra = reference[a].array_distance(pr.center);
+            let rb = reference[b].array_distance(pr.center);
+            let rab = reference[a].array_distance(reference[b]);
+            let near_a = nearest_anchors(class_of(a), |j| (mobile[j].array_distance(pm.center) - ra).abs());
+            for am in near_a {
+                let near_b = nearest_anchors(class_of(b), |j| {
+                    if j == am {
+                        return f64::INFINITY;
+                    }
+                    let db = mobile[j].array_distance(pm.center) - rb;
+                    let dab = mobile[j].array_distance(mobile[am]) - rab;
+                    db.hypot(dab)
+                });
+                for bm in near_b.into_iter().filter(|&bm| bm != am) {
+                    if let Some(fm) = frame(pm.center, mobile[am], mobile[bm]) {
+                        guesses.push(superposition(fr.transpose().matmul(&fm), centers, f64::INFINITY));
+                    }
+                }
+            }
+        }
+    }
+
+    // a start matching within rounding errors cannot be improved on
+    let size = reference
+        .iter()
+        .fold(0.0, |s: f64, p| s.max(p.array_distance(pr.center)));
+    let exact = EXACT_RMSD * size;
+    let mut best: Option<PermutedSuperposition> = None;
+    for mut sp in guesses {
+        let mut permutation = matching(&sp)?;
+        for _ in 0..max_iter {
+            sp = fit(&permutation);
+            let next = matching(&sp)?;
+            if next == permutation {
+                break;
+            }
+            permutation = next;
+        }
+        let sp = fit(&permutation);
+        if best.as_ref().is_none_or(|b| sp.rmsd < b.superposition.rmsd) {
+            best = Some(PermutedSuperposition {
+                superposition: sp,
+                permutation,
+            });
+        }
+        if best.as_ref().is_some_and(|b| b.superposition.rmsd <= exact) {
+            break;
+        }
+    }
+    best
+}
+
+/// Moments of inertia within this fraction of the largest one are taken as
+/// degenerate when choosing initial guesses in [`superpose_permuted`].
+const DEGENERATE_MOMENTS: f64 = 0.05;
+
+/// The number of mobile points tried for each anchor point of a frame in
+/// [`superpose_permuted`].
+const MAX_ANCHOR_CANDIDATES: usize = 4;
+
+/// An RMSD below this fraction of the size of the reference is taken as an
+/// exact match in [`superpose_permuted`], which ends the search.
+const EXACT_RMSD: f64 = 1e-6;
+
+/// Return up to [`MAX_ANCHOR_CANDIDATES`] of `candidates` with the smallest
+/// `mismatch`.
+fn nearest_anchors(candidates: &[usize], mismatch: impl Fn(usize) -> f64) -> Vec<usize> {
+    let mut scored: Vec<_> = candidates.iter().map(|&j| (mismatch(j), j)).collect();
+    scored.sort_by(|x, y| x.0.total_cmp(&y.0));
+    scored.into_iter().take(MAX_ANCHOR_CANDIDATES).map(|(_, j)| j).collect()
+}
+
+/// Choose two reference points spanning a frame with `center`, taken from
+/// the smallest classes so that few mobile pairs need to be tried. The
+/// second point is kept well off the line through the first one.
+fn frame_anchors<L>(
+    points: &[[f64; 3]],
+    center: [f64; 3],
+    classes: &[(&L, Vec<usize>, Vec<usize>)],
+) -> Option<(usize, usize)> {
+    let size = points.iter().fold(0.0, |s: f64, p| s.max(p.array_distance(center)));
+    let mut order: Vec<_> = classes.iter().map(|c| &c.1).collect();
+    order.sort_by_key(|ir| ir.len());
+    // the point of a class with the largest `dist`, if large enough
+    let farthest = |ir: &[usize], dist: &dyn Fn([f64; 3]) -> f64| {
+        ir.iter()
+            .map(|&i| (i, dist(points[i])))
+            .max_by(|x, y| x.1.total_cmp(&y.1))
+            .filter(|&(_, d)| d > 0.1 * size)
+    };
+    // take the first point from the smallest class possible, and the
+    // second as far as possible from the line through the first
+    let (a, _) = order
+        .iter()
+        .find_map(|ir| farthest(ir, &|p| p.array_distance(center)))?;
+    let axis = points[a].array_sub(center).array_normalize()?;
+    let off_axis = |p: [f64; 3]| {
+        let r = p.array_sub(center);
+        r.array_sub(axis.array_scale(r.array_dot(axis))).array_norm()
+    };
+    let (b, _) = order.iter().find_map(|ir| farthest(ir, &off_axis))?;
+    Some((a, b))
+}
+
+/// Return the rows of the right-handed orthonormal frame with the first axis
+/// along `a - origin` and the second in the plane of `origin`, `a` and `b`,
+/// or `None` if the three points are collinear.
+fn frame(origin: [f64; 3], a: [f64; 3], b: [f64; 3]) -> Option<Mat3> {
+    let x = a.array_sub(origin).array_normalize()?;
+    let r = b.array_sub(origin);
+    let y = r.array_sub(x.array_scale(r.array_dot(x))).array_normalize()?;
+    Some([x, y, x.array_cross(y)])
+}
+// 71b4c2e8 ends here
+
+// [[file:../vecfx.note::e7c0a394][e7c0a394]]
+#[test]
+fn test_superpose() {
+    use approx::*;
+
+    let reference = [
+        [0.0, 0.0, 0.1173],
+        [0.0, 0.7572, -0.4692],
+        [0.0, -0.7572, -0.4692],
+        [1.2, 0.3, 0.8],
+        [-0.4, 1.1, 2.0],
+    ];
+    let rot = [[0.36, 0.48, -0.80], [-0.80, 0.60, 0.00], [0.48, 0.64, 0.60]];
+    let mut mobile = reference;
+    rot.apply_to(&mut mobile);
+    mobile.translate([3.0, -1.0, 2.0]);
+    // some noise
+    mobile[3][0] += 0.1;
+    mobile[4][2] -= 0.05;
+    let weights = [16.0, 1.0, 1.0, 12.0, 14.0];
+
+    for w in [None, Some(&weights[..])] {
+        let qcp = superpose_qcp(&reference, &mobile, w);
+        let mut aligned = mobile;
+        qcp.apply(&mut aligned);
+        assert_relative_eq!(qcp.rmsd, rmsd(&reference, &aligned, w), epsilon = 1e-8);
+        assert_relative_eq!(qcp.rotation.det(), 1.0, epsilon = 1e-12);
+        assert!(qcp.rmsd < rmsd(&reference, &mobile, w));
+
+        #[cfg(feature = "nalgebra")]
+        {
+            let kabsch = superpose_kabsch(&reference, &mobile, w);
+            assert_relative_eq!(kabsch.rmsd, qcp.rmsd, epsilon = 1e-8);
+            for (a, b) in kabsch.rotation.iter().zip(&qcp.rotation) {
+                assert_relative_eq!(a.array_distance(*b), 0.0, epsilon = 1e-8);
+            }
+            assert_relative_eq!(kabsch.translation.array_distance(qcp.translation), 0.0, epsilon = 1e-8);
+        }
+    }
+
+    // NaN coordinates give a NaN rmsd, not a perfect match
+    let mut broken = reference;
+    broken[1][2] = f64::NAN;
+    assert!(superpose_qcp(&reference, &broken, None).rmsd.is_nan());
+    assert!(align(&reference, &mut broken.clone(), None).rmsd.is_nan());
+    #[cfg(feature = "nalgebra")]
+    assert!(superpose_kabsch(&reference, &broken, None).rmsd.is_nan());
+
+    // exact match, also for degenerate collinear points
+    let mut mobile = reference;
+    rot.apply_to(&mut mobile);
+    let sp = align(&reference, &mut mobile, None);
+    assert_relative_eq!(sp.rmsd, 0.0, epsilon = 1e-6);
+    assert_relative_eq!(rmsd(&reference, &mobile, None), 0.0, epsilon = 1e-10);
+
+    let line = [[0.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 0.0, 3.0]];
+    let mut mobile = line;
+    rot.apply_to(&mut mobile);
+    align(&line, &mut mobile, None);
+    assert_relative_eq!(rmsd(&line, &mobile, None), 0.0, epsilon = 1e-10);
+}
+// e7c0a394 ends here
+
+// [[file:../vecfx.note::c3a87f12][c3a87f12]]
+#[test]
+fn test_superpose_permuted() {
+    use approx::*;
+
+    // methane with distorted hydrogens
+    let reference = [
+        [0.0, 0.0, 0.0],
+        [0.63, 0.63, 0.63],
+        [-0.63, -0.63, 0.60],
+        [-0.60, 0.63, -0.63],
+        [0.63, -0.66, -0.63],
+    ];
+    let labels = ["C", "H", "H", "H", "H"];
+    let rot = [[0.36, 0.48, -0.80], [-0.80, 0.60, 0.00], [0.48, 0.64, 0.60]];
+    let order = [3, 0, 4, 1, 2];
+    let mut mobile: Vec<_> = order.iter().map(|&k| reference[k]).collect();
+    rot.apply_to(&mut mobile);
+    mobile.translate([1.0, 2.0, -3.0]);
+    let mobile_labels: Vec<_> = order.iter().map(|&k| labels[k]).collect();
+
+    // the given order does not match
+    assert!(superpose_qcp(&reference, &mobile, None).rmsd > 0.1);
+    let sp = superpose_permuted(&reference, &mobile, &labels, &mobile_labels, None, 20).unwrap();
+    assert_relative_eq!(sp.superposition.rmsd, 0.0, epsilon = 1e-6);
+    for (i, &j) in sp.permutation.iter().enumerate() {
+        assert_eq!(order[j], i);
+    }
+    let aligned = sp.apply(&mobile);
+    assert_relative_eq!(rmsd(&reference, &aligned, None), 0.0, epsilon = 1e-6);
+
+    // weighted
+    let masses = [12.0, 1.0, 1.0, 1.0, 1.0];
+    let sp = superpose_permuted(&reference, &mobile, &labels, &mobile_labels, Some(&masses), 20).unwrap();
+    assert_relative_eq!(sp.superposition.rmsd, 0.0, epsilon = 1e-6);
+
+    // incompatible classes
+    let other = ["C", "H", "H", "H", "O"];
+    assert!(superpose_permuted(&reference, &mobile, &labels, &other, None, 20).is_none());
+
+    // a NaN coordinate is rejected instead of hanging the assignment
+    let mut broken = mobile.clone();
+    broken[2][1] = f64::NAN;
+    assert!(superpose_permuted(&reference, &broken, &labels, &mobile_labels, None, 20).is_none());
+
+    // a perfect tetrahedron, whose principal axes are arbitrary
+    let d = 0.63;
+    let methane = [[0.0, 0.0, 0.0], [d, d, d], [-d, -d, d], [-d, d, -d], [d, -d, -d]];
+    let mut mobile: Vec<_> = order.iter().map(|&k| methane[k]).collect();
+    rot.apply_to(&mut mobile);
+    let sp = superpose_permuted(&methane, &mobile, &labels, &mobile_labels, None, 20).unwrap();
+    assert_relative_eq!(sp.superposition.rmsd, 0.0, epsilon = 1e-6);
+
+    // an irregular cloud of points whose moments are equal by accident
+    use crate::{gyration_tensor, symmetric_eigen3};
+    let n = 30;
+    let mut cloud: Vec<[f64; 3]> = (0..n)
+        .map(|k| k as f64)
+        .map(|k| {
+            [
+                3.0 * (1.3 * k).sin(),
+                2.0 * (2.9 * k + 1.0).sin(),
+                (4.1 * k + 2.0).sin(),
+            ]
+        })
+        .collect();
+    cloud.recenter([0.0; 3], None, None);
+    // stretch along the principal axes to an isotropic gyration tensor
+    let eigen = symmetric_eigen3(gyration_tensor(&cloud, None));
+    let stretch: Mat3 = std::array::from_fn(|i| eigen.eigenvectors[i].array_scale(eigen.eigenvalues[i].sqrt().recip()));
+    eigen.eigenvectors.transpose().matmul(&stretch).apply_to(&mut cloud);
+    let cloud_labels: Vec<_> = (0..n).map(|k| if k % 3 == 0 { "A" } else { "B" }).collect();
+    assert_eq!(
+        principal_axes(&cloud, &vec![1.0; n]).rotor_type(1e-8),
+        RotorType::SphericalTop
+    );
+
+    let order: Vec<_> = (0..n).map(|k| (7 * k + 3) % n).collect();
+    let mut mobile: Vec<_> = order.iter().map(|&k| cloud[k]).collect();
+    rot.apply_to(&mut mobile);
+    // noise that does not rotate with the points
+    for (k, p) in mobile.iter_mut().enumerate() {
+        p[k % 3] += 0.01 * (k as f64).cos();
+    }
+    let mobile_labels: Vec<_> = order.iter().map(|&k| cloud_labels[k]).collect();
+    let matched: Vec<_> = (0..n)
+        .map(|i| mobile[order.iter().position(|&k| k == i).unwrap()])
+        .collect();
+    let sp = superpose_permuted(&cloud, &mobile, &cloud_labels, &mobile_labels, None, 20).unwrap();
+    assert_relative_eq!(
+        sp.superposition.rmsd,
+        superpose_qcp(&cloud, &matched, None).rmsd,
+        epsilon = 1e-9
+    );
+}
+// c3a87f12 ends here
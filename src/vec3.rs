@@ -0,0 +1,310 @@
+// [[file:../vecfx.note::7b2e4d90][7b2e4d90]]
+use crate::{Array3MathExt, ArrayMathExt, Float};
+
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
+
+#[cfg(feature = "nalgebra")]
+use nalgebra as na;
+// 7b2e4d90 ends here
+
+// [[file:../vecfx.note::c4a81f36][c4a81f36]]
+/// A 3D vector with arithmetic operators, sharing the memory layout of
+/// `[F; 3]`.
+///
+/// # Example
+/// ```
+/// use vecfx::*;
+///
+/// let positions = [[0.0, 0.0, 0.0], [1.0, 2.0, 3.0]];
+/// let [p1, p2] = [Vec3::from(positions[0]), Vec3::from(positions[1])];
+/// let mid = (p1 + p2) * 0.5;
+/// assert_eq!(mid, Vec3::new(0.5, 1.0, 1.5));
+///
+/// // zero-cost view of a list of points
+/// let points = Vec3::from_arrays(&positions);
+/// assert_eq!(points.iter().copied().sum::<Vec3>(), p2);
+/// ```
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct Vec3<F = f64>(pub [F; 3]);
+
+impl<F: Float + std::fmt::Debug> Vec3<F> {
+    /// Construct from components.
+    pub fn new(x: F, y: F, z: F) -> Self {
+        Self([x, y, z])
+    }
+
+    /// The zero vector.
+    pub fn zero() -> Self {
+        Self([F::zero(); 3])
+    }
+
+    pub fn x(&self) -> F {
+        self.0[0]
+    }
+
+    pub fn y(&self) -> F {
+        self.0[1]
+    }
+
+    pub fn z(&self) -> F {
+        self.0[2]
+    }
+
+    /// Return the components as plain array.
+    pub fn to_array(self) -> [F; 3] {
+        self.0
+    }
+
+    pub fn dot(self, other: Self) -> F {
+        self.0.array_dot(other.0)
+    }
+
+    pub fn cross(self, other: Self) -> Self {
+        Self(self.0.array_cross(other.0))
+    }
+
+    pub fn norm(self) -> F {
+        self.0.array_norm()
+    }
+
+    pub fn norm_squared(self) -> F {
+        self.0.array_norm_squared()
+    }
+
+    /// Return a unit vector along self, or `None` for a zero vector or one
+    /// with non-finite components.
+    pub fn normalize(self) -> Option<Self> {
+        self.0.array_normalize().map(Self)
+    }
+
+    pub fn distance(self, other: Self) -> F {
+        self.0.array_distance(other.0)
+    }
+}
+
+impl<F> Vec3<F> {
+    /// View a list of points as `&[Vec3]` without copying.
+    pub fn from_arrays(points: &[[F; 3]]) -> &[Self] {
+        // SAFETY: Vec3<F> is repr(transparent) over [F; 3]
+        unsafe { std::slice::from_raw_parts(points.as_ptr() as *const Self, points.len()) }
+    }
+
+    /// View a mutable list of points as `&mut [Vec3]` without copying.
+    pub fn from_arrays_mut(points: &mut [[F; 3]]) -> &mut [Self] {
+        // SAFETY: Vec3<F> is repr(transparent) over [F; 3]
+        unsafe { std::slice::from_raw_parts_mut(points.as_mut_ptr() as *mut Self, points.len()) }
+    }
+
+    /// View `&[Vec3]` as `&[[F; 3]]` without copying.
+    pub fn as_arrays(points: &[Self]) -> &[[F; 3]] {
+        // SAFETY: Vec3<F> is repr(transparent) over [F; 3]
+        unsafe { std::slice::from_raw_parts(points.as_ptr() as *const [F; 3], points.len()) }
+    }
+
+    /// View `&mut [Vec3]` as `&mut [[F; 3]]` without copying.
+    pub fn as_arrays_mut(points: &mut [Self]) -> &mut [[F; 3]] {
+        // SAFETY: Vec3<F> is repr(transparent) over [F; 3]
+        unsafe { std::slice::from_raw_parts_mut(points.as_mut_ptr() as *mut [F; 3], points.len()) }
+    }
+}
+// c4a81f36 ends here
+
+// [[file:../vecfx.note::e05d9b2a][e05d9b2a]]
+impl<F: Float + std::fmt::Debug> Add for Vec3<F> {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self(self.0.array_add(other.0))
+    }
+}
+
+impl<F: Float + std::fmt::Debug> Sub for Vec3<F> {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0.array_sub(other.0))
+    }
+}
+
+impl<F: Float + std::fmt::Debug> Neg for Vec3<F> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self(self.0.array_neg())
+    }
+}
+
+impl<F: Float + std::fmt::Debug> Mul<F> for Vec3<F> {
+    type Output = Self;
+
+    fn mul(self, value: F) -> Self {
+        Self(self.0.array_scale(value))
+    }
+}
+
+impl<F: Float + std::fmt::Debug> Div<F> for Vec3<F> {
+    type Output = Self;
+
+    fn div(self, value: F) -> Self {
+        Self(self.0.map(|x| x / value))
+    }
+}
+
+macro_rules! impl_scalar_mul {
+    ($($t:ty),*) => {
+        $(
+            impl Mul<Vec3<$t>> for $t {
+                type Output = Vec3<$t>;
+
+                fn mul(self, v: Vec3<$t>) -> Vec3<$t> {
+                    v * self
+                }
+            }
+        )*
+    };
+}
+
+impl_scalar_mul!(f32, f64);
+
+impl<F: Float + std::fmt::Debug> AddAssign for Vec3<F> {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl<F: Float + std::fmt::Debug> SubAssign for Vec3<F> {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl<F: Float + std::fmt::Debug> MulAssign<F> for Vec3<F> {
+    fn mul_assign(&mut self, value: F) {
+        *self = *self * value;
+    }
+}
+
+impl<F: Float + std::fmt::Debug> DivAssign<F> for Vec3<F> {
+    fn div_assign(&mut self, value: F) {
+        *self = *self / value;
+    }
+}
+
+impl<F> Index<usize> for Vec3<F> {
+    type Output = F;
+
+    fn index(&self, i: usize) -> &F {
+        &self.0[i]
+    }
+}
+
+impl<F> IndexMut<usize> for Vec3<F> {
+    fn index_mut(&mut self, i: usize) -> &mut F {
+        &mut self.0[i]
+    }
+}
+
+impl<F: Float + std::fmt::Debug> Sum for Vec3<F> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |s, v| s + v)
+    }
+}
+
+impl<'a, F: Float + std::fmt::Debug> Sum<&'a Vec3<F>> for Vec3<F> {
+    fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+        iter.fold(Self::zero(), |s, v| s + *v)
+    }
+}
+
+impl<F> From<[F; 3]> for Vec3<F> {
+    fn from(x: [F; 3]) -> Self {
+        Self(x)
+    }
+}
+
+impl<F> From<Vec3<F>> for [F; 3] {
+    fn from(v: Vec3<F>) -> Self {
+        v.0
+    }
+}
+
+impl<F> AsRef<[F]> for Vec3<F> {
+    fn as_ref(&self) -> &[F] {
+        &self.0
+    }
+}
+
+impl<F> AsMut<[F]> for Vec3<F> {
+    fn as_mut(&mut self) -> &mut [F] {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl<F: na::Scalar + Copy> From<na::Vector3<F>> for Vec3<F> {
+    fn from(v: na::Vector3<F>) -> Self {
+        Self([v[0], v[1], v[2]])
+    }
+}
+
+#[cfg(feature = "nalgebra")]
+impl<F: na::Scalar> From<Vec3<F>> for na::Vector3<F> {
+    fn from(v: Vec3<F>) -> Self {
+        na::Vector3::from(v.0)
+    }
+}
+// e05d9b2a ends here
+
+// [[file:../vecfx.note::51f3ac86][51f3ac86]]
+#[test]
+fn test_vec3() {
+    use crate::VecFloatAs3D;
+
+    let a = Vec3::new(1.0, 2.0, 3.0);
+    let b: Vec3 = [0.5, 0.5, 0.5].into();
+    assert_eq!(a + b, Vec3::new(1.5, 2.5, 3.5));
+    assert_eq!(a - b, Vec3::new(0.5, 1.5, 2.5));
+    assert_eq!(-a, Vec3::new(-1.0, -2.0, -3.0));
+    assert_eq!(2.0 * a, a * 2.0);
+    assert_eq!((a * 2.0) / 2.0, a);
+    assert_eq!(a.cross(Vec3::new(1.0, 0.0, 0.0)), Vec3::new(0.0, 3.0, -2.0));
+    assert_eq!(a.dot(b), 3.0);
+    assert_eq!(a[1], 2.0);
+
+    let mut c = a;
+    c += b;
+    c -= a;
+    c *= 4.0;
+    c[2] = 0.0;
+    assert_eq!(<[f64; 3]>::from(c), [2.0, 2.0, 0.0]);
+
+    // views share memory with the arrays
+    let mut positions = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0];
+    let points = positions.as_vec3();
+    assert_eq!(points.iter().sum::<Vec3>(), Vec3::new(5.0, 7.0, 9.0));
+    assert_eq!(Vec3::as_arrays(points), positions.as_3d());
+    positions.as_mut_vec3()[1] -= Vec3::new(4.0, 5.0, 6.0);
+    assert_eq!(positions, [1.0, 2.0, 3.0, 0.0, 0.0, 0.0]);
+
+    #[cfg(feature = "nalgebra")]
+    {
+        let v: crate::Vector3f = a.into();
+        assert_eq!(Vec3::from(v), a);
+    }
+}
+// 51f3ac86 ends here
+
+// [[file:../vecfx.note::9a6f0e13][9a6f0e13]]
+#[test]
+#[cfg(feature = "serde")]
+fn test_vec3_serde() {
+    let v = Vec3::new(1.0, 2.5, -3.0);
+    let s = serde_json::to_string(&v).unwrap();
+    assert_eq!(s, "[1.0,2.5,-3.0]");
+    let w: Vec3 = serde_json::from_str(&s).unwrap();
+    assert_eq!(v, w);
+}
+// 9a6f0e13 ends here
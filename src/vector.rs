@@ -1,4 +1,7 @@
 // [[file:../vecfx.note::*imports][imports:1]]
+use crate::kernel::KernelFloat;
+use crate::{ArrayMathExt, Float, Vec3};
+
 #[cfg(feature = "nalgebra")]
 use nalgebra as na;
 // imports:1 ends here
@@ -23,113 +26,302 @@ pub type Matrix3f = na::Matrix3<f64>;
 // types:1 ends here
 
 // [[file:../vecfx.note::*for Vec<f64>][for Vec<f64>:1]]
+/// Error returned when two slices involved in a vector operation differ in
+/// length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DimensionMismatch {
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl std::fmt::Display for DimensionMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "dimension mismatch: expected {}, found {}", self.expected, self.found)
+    }
+}
+
+impl std::error::Error for DimensionMismatch {}
+
+fn check_dimension(expected: usize, found: usize) -> Result<(), DimensionMismatch> {
+    if expected == found {
+        Ok(())
+    } else {
+        Err(DimensionMismatch { expected, found })
+    }
+}
+
 /// Abstracting simple vector based math operations
-pub trait VecFloatExt {
+///
+/// All slices involved in an operation must have the same length. This
+/// is checked by debug assertions only; use the `try_*` variants to get a
+/// [`DimensionMismatch`] error instead.
+pub trait VecFloatExt<F: Float = f64> {
     /// y += c*x
-    fn vecadd(&mut self, x: &[f64], c: f64);
+    fn vecadd(&mut self, x: &[F], c: F);
 
     /// vector dot product
     /// s = x.dot(y)
-    fn vecdot(&self, other: &[f64]) -> f64;
+    ///
+    /// The sum is accumulated in several independent lanes for speed, so the
+    /// result may differ from a sequential sum in the last few bits.
+    fn vecdot(&self, other: &[F]) -> F;
 
     /// y = z
-    fn veccpy(&mut self, x: &[f64]);
+    fn veccpy(&mut self, x: &[F]);
 
     /// y = -x
-    fn vecncpy(&mut self, x: &[f64]);
+    fn vecncpy(&mut self, x: &[F]);
 
     /// z = x - y
-    fn vecdiff(&mut self, x: &[f64], y: &[f64]);
+    fn vecdiff(&mut self, x: &[F], y: &[F]);
 
     /// y *= c
-    fn vecscale(&mut self, c: f64);
+    fn vecscale(&mut self, c: F);
 
     /// ||x||, L2 norm
-    fn vec2norm(&self) -> f64;
+    ///
+    /// Overflows for components around 1e155; use [`VecFloatExt::vecnrm2`]
+    /// for inputs of extreme magnitude.
+    fn vec2norm(&self) -> F;
 
     /// 1 / ||x||
-    fn vec2norminv(&self) -> f64;
+    fn vec2norminv(&self) -> F;
 
     /// d = ||a-b||
-    fn vecdist(&self, other: &[f64]) -> f64 {
+    fn vecdist(&self, other: &[F]) -> F {
         self.vecdist_squared(other).sqrt()
     }
 
     /// d = ||a-b||^2
-    fn vecdist_squared(&self, other: &[f64]) -> f64;
+    fn vecdist_squared(&self, other: &[F]) -> F;
+
+    /// y = a*x + b*y
+    fn vecaxpby(&mut self, x: &[F], a: F, b: F);
+
+    /// sum(|x_i|), L1 norm as in BLAS asum
+    fn vecasum(&self) -> F;
+
+    /// Index to the first element with the largest absolute value.
+    ///
+    /// # Panics
+    ///
+    /// * if self is empty.
+    fn veciamax(&self) -> usize;
+
+    /// Apply a Givens rotation to the pairs (x_i, y_i) with x being self:
+    /// x = c*x + s*y, y = c*y - s*x
+    fn vecrot(&mut self, y: &mut [F], c: F, s: F);
+
+    /// Swap the elements of two slices.
+    fn vecswap(&mut self, x: &mut [F]);
+
+    /// ||x||, L2 norm computed with scaling as in reference BLAS nrm2, which
+    /// avoids overflow and underflow at the cost of speed.
+    fn vecnrm2(&self) -> F;
+
+    /// y += c*x, checking for equal lengths
+    fn try_vecadd(&mut self, x: &[F], c: F) -> Result<(), DimensionMismatch>;
+
+    /// s = x.dot(y), checking for equal lengths
+    fn try_vecdot(&self, other: &[F]) -> Result<F, DimensionMismatch>;
+
+    /// y = x, checking for equal lengths
+    fn try_veccpy(&mut self, x: &[F]) -> Result<(), DimensionMismatch>;
+
+    /// y = -x, checking for equal lengths
+    fn try_vecncpy(&mut self, x: &[F]) -> Result<(), DimensionMismatch>;
+
+    /// z = x - y, checking for equal lengths
+    fn try_vecdiff(&mut self, x: &[F], y: &[F]) -> Result<(), DimensionMismatch>;
+
+    /// d = ||a-b||, checking for equal lengths
+    fn try_vecdist(&self, other: &[F]) -> Result<F, DimensionMismatch> {
+        self.try_vecdist_squared(other).map(F::sqrt)
+    }
+
+    /// d = ||a-b||^2, checking for equal lengths
+    fn try_vecdist_squared(&self, other: &[F]) -> Result<F, DimensionMismatch>;
 
     #[cfg(feature = "nalgebra")]
     /// Create dynamically allocated column vector from self
-    fn to_column_vector(&self) -> na::DVector<f64>;
+    fn to_column_vector(&self) -> na::DVector<F>;
 
     #[cfg(feature = "nalgebra")]
     /// Create dynamically allocated column vector from self
-    fn to_vector(&self) -> na::DVector<f64> {
+    fn to_vector(&self) -> na::DVector<F> {
         self.to_column_vector()
     }
 }
 
-impl VecFloatExt for [f64] {
+impl<F> VecFloatExt<F> for [F]
+where
+    F: KernelFloat + std::fmt::Debug + 'static,
+{
     /// y += c*x
-    fn vecadd(&mut self, x: &[f64], c: f64) {
-        for (y, x) in self.iter_mut().zip(x) {
-            *y += c * x;
-        }
+    fn vecadd(&mut self, x: &[F], c: F) {
+        debug_assert_eq!(self.len(), x.len(), "vecadd: dimension mismatch");
+        crate::kernel::axpy(self, x, c);
     }
 
     /// s = y.dot(x)
-    fn vecdot(&self, other: &[f64]) -> f64 {
-        self.iter().zip(other).map(|(x, y)| x * y).sum()
+    fn vecdot(&self, other: &[F]) -> F {
+        debug_assert_eq!(self.len(), other.len(), "vecdot: dimension mismatch");
+        crate::kernel::dot(self, other)
     }
 
     /// y *= c
-    fn vecscale(&mut self, c: f64) {
-        for y in self.iter_mut() {
-            *y *= c;
-        }
+    fn vecscale(&mut self, c: F) {
+        crate::kernel::scale(self, c);
     }
 
     /// y = x
-    fn veccpy(&mut self, x: &[f64]) {
+    fn veccpy(&mut self, x: &[F]) {
+        debug_assert_eq!(self.len(), x.len(), "veccpy: dimension mismatch");
         for (v, x) in self.iter_mut().zip(x) {
             *v = *x;
         }
     }
 
     /// y = -x
-    fn vecncpy(&mut self, x: &[f64]) {
-        for (v, x) in self.iter_mut().zip(x) {
+    fn vecncpy(&mut self, x: &[F]) {
+        debug_assert_eq!(self.len(), x.len(), "vecncpy: dimension mismatch");
+        for (v, &x) in self.iter_mut().zip(x) {
             *v = -x;
         }
     }
 
     /// z = x - y
-    fn vecdiff(&mut self, x: &[f64], y: &[f64]) {
-        for ((z, x), y) in self.iter_mut().zip(x).zip(y) {
+    fn vecdiff(&mut self, x: &[F], y: &[F]) {
+        debug_assert_eq!(self.len(), x.len(), "vecdiff: dimension mismatch");
+        debug_assert_eq!(self.len(), y.len(), "vecdiff: dimension mismatch");
+        for ((z, &x), &y) in self.iter_mut().zip(x).zip(y) {
             *z = x - y;
         }
     }
 
     /// ||x||, L2 norm
-    fn vec2norm(&self) -> f64 {
-        let n2 = self.vecdot(&self);
+    fn vec2norm(&self) -> F {
+        let n2 = self.vecdot(self);
         n2.sqrt()
     }
 
     /// 1/||x||
-    fn vec2norminv(&self) -> f64 {
-        1.0 / self.vec2norm()
+    fn vec2norminv(&self) -> F {
+        self.vec2norm().recip()
     }
 
     /// d = ||a-b||^2
-    fn vecdist_squared(&self, other: &[f64]) -> f64 {
-        self.iter().zip(other).map(|(a, b)| (a - b).powi(2)).sum::<f64>()
+    fn vecdist_squared(&self, other: &[F]) -> F {
+        debug_assert_eq!(self.len(), other.len(), "vecdist_squared: dimension mismatch");
+        crate::kernel::dist_squared(self, other)
+    }
+
+    /// y = a*x + b*y
+    fn vecaxpby(&mut self, x: &[F], a: F, b: F) {
+        debug_assert_eq!(self.len(), x.len(), "vecaxpby: dimension mismatch");
+        for (y, &x) in self.iter_mut().zip(x) {
+            *y = a * x + b * *y;
+        }
+    }
+
+    /// sum(|x_i|)
+    fn vecasum(&self) -> F {
+        self.iter().fold(F::zero(), |s, x| s + x.abs())
+    }
+
+    fn veciamax(&self) -> usize {
+        assert!(!self.is_empty(), "veciamax: empty slice");
+        let mut imax = 0;
+        for (i, x) in self.iter().enumerate() {
+            if x.abs() > self[imax].abs() {
+                imax = i;
+            }
+        }
+        imax
+    }
+
+    fn vecrot(&mut self, y: &mut [F], c: F, s: F) {
+        debug_assert_eq!(self.len(), y.len(), "vecrot: dimension mismatch");
+        for (x, y) in self.iter_mut().zip(y.iter_mut()) {
+            let t = c * *x + s * *y;
+            *y = c * *y - s * *x;
+            *x = t;
+        }
+    }
+
+    fn vecswap(&mut self, x: &mut [F]) {
+        debug_assert_eq!(self.len(), x.len(), "vecswap: dimension mismatch");
+        for (y, x) in self.iter_mut().zip(x.iter_mut()) {
+            std::mem::swap(y, x);
+        }
+    }
+
+    fn vecnrm2(&self) -> F {
+        let mut scale = F::zero();
+        let mut ssq = F::one();
+        for x in self.iter().filter(|x| !x.is_zero()) {
+            let absx = x.abs();
+            if scale < absx {
+                ssq = F::one() + ssq * (scale / absx).powi(2);
+                scale = absx;
+            } else {
+                ssq = ssq + (absx / scale).powi(2);
+            }
+        }
+        scale * ssq.sqrt()
+    }
+
+    fn try_vecadd(&mut self, x: &[F], c: F) -> Result<(), DimensionMismatch> {
+        check_dimension(self.len(), x.len())?;
+        self.vecadd(x, c);
+        Ok(())
+    }
+
+    fn try_vecdot(&self, other: &[F]) -> Result<F, DimensionMismatch> {
+        check_dimension(self.len(), other.len())?;
+        Ok(self.vecdot(other))
+    }
+
+    fn try_veccpy(&mut self, x: &[F]) -> Result<(), DimensionMismatch> {
+        check_dimension(self.len(), x.len())?;
+        self.veccpy(x);
+        Ok(())
+    }
+
+    fn try_vecncpy(&mut self, x: &[F]) -> Result<(), DimensionMismatch> {
+        check_dimension(self.len(), x.len())?;
+        self.vecncpy(x);
+        Ok(())
+    }
+
+    fn try_vecdiff(&mut self, x: &[F], y: &[F]) -> Result<(), DimensionMismatch> {
+        check_dimension(self.len(), x.len())?;
+        check_dimension(self.len(), y.len())?;
+        self.vecdiff(x, y);
+        Ok(())
+    }
+
+    fn try_vecdist_squared(&self, other: &[F]) -> Result<F, DimensionMismatch> {
+        check_dimension(self.len(), other.len())?;
+        Ok(self.vecdist_squared(other))
     }
 
     #[cfg(feature = "nalgebra")]
-    fn to_column_vector(&self) -> na::DVector<f64> {
-        na::DVector::from_column_slice(&self)
+    fn to_column_vector(&self) -> na::DVector<F> {
+        na::DVector::from_column_slice(self)
+    }
+}
+
+/// Construct a Givens rotation as in BLAS rotg. Returns `(c, s, r)` such
+/// that `[c s; -s c] * [a; b] = [r; 0]`.
+pub fn rotg<F: Float>(a: F, b: F) -> (F, F, F) {
+    let scale = a.abs() + b.abs();
+    if scale.is_zero() {
+        return (F::one(), F::zero(), F::zero());
     }
+    let roe = if a.abs() > b.abs() { a } else { b };
+    let r = scale * ((a / scale).powi(2) + (b / scale).powi(2)).sqrt() * roe.signum();
+    (a / r, b / r, r)
 }
 
 #[test]
@@ -158,7 +350,7 @@ fn test_vec_math() {
     assert_eq!(10.0, y[2]);
 
     // vector diff
-    let mut z = y.clone();
+    let mut z = y;
     z.vecdiff(&x, &y);
     assert_eq!(-5.0, z[0]);
     assert_eq!(-7.0, z[1]);
@@ -189,76 +381,247 @@ fn test_vec_math_na() {
     assert_eq!(v.norm_squared(), 3.0);
 }
 
+#[test]
+fn test_vec_math_checked() {
+    let x = [1.0, 2.0, 3.0];
+    let mut y = [1.0, 1.0];
+    assert_eq!(y.try_vecadd(&x, 1.0), Err(DimensionMismatch { expected: 2, found: 3 }));
+    assert!(y.try_vecdot(&x).is_err());
+    assert!(y.try_vecdist(&x).is_err());
+    assert!(y.try_vecdiff(&x[..2], &x).is_err());
+    assert_eq!(y.try_vecdot(&x[..2]), Ok(3.0));
+    y.try_vecncpy(&x[1..]).unwrap();
+    assert_eq!(y, [-2.0, -3.0]);
+}
+
+#[test]
+fn test_vec_blas1() {
+    use approx::*;
+
+    let x = [1.0, -4.0, 2.0];
+    let mut y = [1.0, 1.0, 1.0];
+    y.vecaxpby(&x, 2.0, 3.0);
+    assert_eq!(y, [5.0, -5.0, 7.0]);
+    assert_eq!(x.vecasum(), 7.0);
+    assert_eq!(x.veciamax(), 1);
+    assert_eq!([1.0, -3.0, 3.0].veciamax(), 1);
+
+    let mut a = [1.0, 2.0];
+    let mut b = [3.0, 4.0];
+    a.vecswap(&mut b);
+    assert_eq!(a, [3.0, 4.0]);
+    assert_eq!(b, [1.0, 2.0]);
+
+    let (c, s, r) = rotg(3.0, 4.0);
+    assert_relative_eq!(r, 5.0);
+    let mut xs = [3.0];
+    let mut ys = [4.0];
+    xs.vecrot(&mut ys, c, s);
+    assert_relative_eq!(xs[0], 5.0);
+    assert_relative_eq!(ys[0], 0.0);
+
+    // no overflow for huge components
+    let big = [3e200, 4e200];
+    assert!(big.vec2norm().is_infinite());
+    assert_relative_eq!(big.vecnrm2(), 5e200, max_relative = 1e-12);
+    assert_relative_eq!([1.0, 2.0, 2.0].vecnrm2(), 3.0);
+    assert_eq!([0.0; 3].vecnrm2(), 0.0);
+}
+
+#[test]
+fn test_vec_math_f32() {
+    let x = [1.0f32, 2.0, 2.0];
+    let mut y = [0.5f32; 3];
+    y.vecadd(&x, 2.0);
+    assert_eq!(y, [2.5, 4.5, 4.5]);
+    assert_eq!(x.vec2norm(), 3.0);
+    assert_eq!(x.vecnrm2(), 3.0);
+    assert_eq!(x.vecdist_squared(&[1.0, 2.0, 0.0]), 4.0);
+
+    let mut p: Vec<f32> = vec![1., 2., 3., 4., 5., 6.];
+    p.as_mut_3d()[1][0] = 0.0;
+    assert_eq!(p.as_3d().as_flat(), &[1., 2., 3., 0., 5., 6.]);
+
+    #[cfg(feature = "nalgebra")]
+    {
+        let v = x.to_vector();
+        assert_eq!(v.norm(), 3.0f32);
+        let m = p.as_3d().to_matrix();
+        assert_eq!(m.as_3d(), p.as_3d());
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn test_vec_math_mismatch() {
+    let _ = [1.0, 2.0].vecdot(&[1.0, 2.0, 3.0]);
+}
+
+/// View a flat slice as nested N-dimensional arrays, such as 2D projections,
+/// 4D homogeneous coordinates or 6-component stress tensors.
+///
+/// The views are safe: `[F; N]` has the same layout as N consecutive `F`
+/// without padding, which is guaranteed by the language.
+///
+/// # Panics
+/// if the slice size is not a multiple of `N`, or `N` is zero.
+pub trait VecFloatAsND<F: Float = f64> {
+    /// View `&[f64]` as `&[[f64; N]]` without copying.
+    fn as_nd<const N: usize>(&self) -> &[[F; N]];
+
+    /// View `&mut [f64]` as `&mut [[f64; N]]` without copying.
+    fn as_mut_nd<const N: usize>(&mut self) -> &mut [[F; N]];
+}
+
+impl<F: Float> VecFloatAsND<F> for [F] {
+    fn as_nd<const N: usize>(&self) -> &[[F; N]] {
+        let (chunks, rest) = self.as_chunks::<N>();
+        assert!(rest.is_empty(), "cannot view slice of length {} as &[[_; {N}]]", self.len());
+        chunks
+    }
+
+    fn as_mut_nd<const N: usize>(&mut self) -> &mut [[F; N]] {
+        let n = self.len();
+        let (chunks, rest) = self.as_chunks_mut::<N>();
+        assert!(rest.is_empty(), "cannot view slice of length {n} as &[[_; {N}]]");
+        chunks
+    }
+}
+
 /// View a flat slice as nested 3D array
 ///
 /// # Panics
 /// if the slice size is incorrect.
-pub trait VecFloatAs3D {
+pub trait VecFloatAs3D<F: Float = f64> {
     /// View `&[f64]` as `&[[f64; 3]]` without copying.
-    fn as_3d(&self) -> &[[f64; 3]];
+    fn as_3d(&self) -> &[[F; 3]];
 
     /// View `&mut [f64]` as `&mut [[f64; 3]]` without copying.
-    fn as_mut_3d(&mut self) -> &mut [[f64; 3]];
+    fn as_mut_3d(&mut self) -> &mut [[F; 3]];
+
+    /// View `&[f64]` as `&[Vec3]` without copying.
+    fn as_vec3(&self) -> &[Vec3<F>] {
+        Vec3::from_arrays(self.as_3d())
+    }
+
+    /// View `&mut [f64]` as `&mut [Vec3]` without copying.
+    fn as_mut_vec3(&mut self) -> &mut [Vec3<F>] {
+        Vec3::from_arrays_mut(self.as_mut_3d())
+    }
 }
 
-impl VecFloatAs3D for [f64] {
-    fn as_3d(&self) -> &[[f64; 3]] {
-        assert_eq!(0, self.len() % 3, "cannot view slice of length {} as &[[_; 3]]", self.len());
-        unsafe { ::std::slice::from_raw_parts(self.as_ptr() as *const _, self.len() / 3) }
+impl<F: Float> VecFloatAs3D<F> for [F] {
+    fn as_3d(&self) -> &[[F; 3]] {
+        self.as_nd()
     }
 
-    fn as_mut_3d(&mut self) -> &mut [[f64; 3]] {
-        assert_eq!(0, self.len() % 3, "cannot view slice of length {} as &[[_; 3]]", self.len());
-        unsafe { ::std::slice::from_raw_parts_mut(self.as_ptr() as *mut _, self.len() / 3) }
+    fn as_mut_3d(&mut self) -> &mut [[F; 3]] {
+        self.as_mut_nd()
     }
 }
 // for Vec<f64>:1 ends here
 
 // [[file:../vecfx.note::*for Vec<\[f64; 3\]>][for Vec<[f64; 3]>:1]]
-pub trait VecFloat3Ext {
-    /// Return a 1-D array, containing the elements of 3xN array
-    fn ravel(&self) -> Vec<f64> {
+/// The former name of [`VecFloatNDExt`], from when it only covered 3D
+/// points.
+pub use self::VecFloatNDExt as VecFloat3Ext;
+
+/// Operations on lists of N-dimensional points stored as `[[f64; N]]`.
+pub trait VecFloatNDExt<F: Float = f64, const N: usize = 3> {
+    /// Return a 1-D array, containing the elements of NxM array
+    fn ravel(&self) -> Vec<F> {
         self.as_flat().to_vec()
     }
 
     /// View as a flat slice
-    fn as_flat(&self) -> &[f64];
+    fn as_flat(&self) -> &[F];
 
     /// View of mut flat slice
-    fn as_mut_flat(&mut self) -> &mut [f64];
+    fn as_mut_flat(&mut self) -> &mut [F];
 
     #[cfg(feature = "nalgebra")]
-    /// Create a 3xN matrix of nalgebra from self
-    fn to_matrix(&self) -> Vector3fVec;
+    /// Create a NxM matrix of nalgebra from self, one point per column.
+    /// For 3D points this is a `Matrix3xX`.
+    fn to_matrix(&self) -> na::OMatrix<F, na::Const<N>, na::Dynamic>
+    where
+        F: na::Scalar;
 
     #[cfg(feature = "nalgebra")]
     #[cfg(feature = "adhoc")]
     /// Return distance matrix
-    fn distance_matrix(&self) -> na::DMatrix<f64>;
+    fn distance_matrix(&self) -> na::DMatrix<F>;
+
+    /// Return the geometric center of all points. The result is NaN for
+    /// an empty set.
+    fn centroid(&self) -> [F; N];
+
+    /// Return the geometric center of the points selected by `indices`.
+    fn centroid_of(&self, indices: &[usize]) -> [F; N];
+
+    /// Return the center of mass of all points.
+    ///
+    /// # Panics
+    ///
+    /// * if `masses` and self differ in length.
+    fn center_of_mass(&self, masses: &[F]) -> [F; N];
+
+    /// Return the center of mass of the points selected by `indices`.
+    /// `masses` is indexed like self, not like `indices`.
+    fn center_of_mass_of(&self, masses: &[F], indices: &[usize]) -> [F; N];
+
+    /// Translate all points by `v` in place.
+    fn translate(&mut self, v: [F; N]);
+
+    /// Translate the points selected by `indices` by `v` in place, leaving
+    /// others untouched.
+    fn translate_subset(&mut self, indices: &[usize], v: [F; N]);
+
+    /// Translate all points in place so that their center lands on `point`,
+    /// and return the translation vector applied. The center is the center
+    /// of mass with `masses` or the centroid without, computed over the
+    /// points selected by `indices` or over all points.
+    ///
+    /// # Example
+    /// ```
+    /// use vecfx::*;
+    ///
+    /// let mut positions = [[1.0, 0.0, 0.0], [3.0, 0.0, 0.0], [9.0, 9.0, 9.0]];
+    /// // center the first two points on the origin
+    /// positions.recenter([0.0; 3], None, Some(&[0, 1]));
+    /// assert_eq!(positions[0], [-1.0, 0.0, 0.0]);
+    /// assert_eq!(positions[2], [7.0, 9.0, 9.0]);
+    /// ```
+    fn recenter(&mut self, point: [F; N], masses: Option<&[F]>, indices: Option<&[usize]>) -> [F; N];
 }
 
-impl VecFloat3Ext for [[f64; 3]] {
+impl<F, const N: usize> VecFloatNDExt<F, N> for [[F; N]]
+where
+    F: KernelFloat + std::fmt::Debug + 'static,
+{
     /// View as a flat slice
-    fn as_flat(&self) -> &[f64] {
-        unsafe { ::std::slice::from_raw_parts(self.as_ptr() as *const _, self.len() * 3) }
+    fn as_flat(&self) -> &[F] {
+        self.as_flattened()
     }
 
     /// View of mut flat slice
-    fn as_mut_flat(&mut self) -> &mut [f64] {
-        unsafe { ::std::slice::from_raw_parts_mut(self.as_mut_ptr() as *mut _, self.len() * 3) }
+    fn as_mut_flat(&mut self) -> &mut [F] {
+        self.as_flattened_mut()
     }
 
     #[cfg(feature = "nalgebra")]
-    /// Create a 3xN matrix of nalgebra from self
-    fn to_matrix(&self) -> Vector3fVec {
-        let r = self.as_flat();
-        Vector3fVec::from_column_slice(r)
+    /// Create a NxM matrix of nalgebra from self
+    fn to_matrix(&self) -> na::OMatrix<F, na::Const<N>, na::Dynamic>
+    where
+        F: na::Scalar,
+    {
+        na::OMatrix::<F, na::Const<N>, na::Dynamic>::from_column_slice(self.as_flat())
     }
 
     /// Return distance matrix
     #[cfg(feature = "nalgebra")]
     #[cfg(feature = "adhoc")]
-    fn distance_matrix(&self) -> na::DMatrix<f64> {
+    fn distance_matrix(&self) -> na::DMatrix<F> {
         let n = self.len();
 
         let mut distances = na::DMatrix::zeros(n, n);
@@ -272,15 +635,98 @@ impl VecFloat3Ext for [[f64; 3]] {
 
         distances
     }
+
+    fn centroid(&self) -> [F; N] {
+        weighted_center(self, None, None)
+    }
+
+    fn centroid_of(&self, indices: &[usize]) -> [F; N] {
+        weighted_center(self, None, Some(indices))
+    }
+
+    fn center_of_mass(&self, masses: &[F]) -> [F; N] {
+        weighted_center(self, Some(masses), None)
+    }
+
+    fn center_of_mass_of(&self, masses: &[F], indices: &[usize]) -> [F; N] {
+        weighted_center(self, Some(masses), Some(indices))
+    }
+
+    fn translate(&mut self, v: [F; N]) {
+        for p in self.iter_mut() {
+            *p = p.array_add(v);
+        }
+    }
+
+    fn translate_subset(&mut self, indices: &[usize], v: [F; N]) {
+        for &i in indices {
+            self[i] = self[i].array_add(v);
+        }
+    }
+
+    fn recenter(&mut self, point: [F; N], masses: Option<&[F]>, indices: Option<&[usize]>) -> [F; N] {
+        let v = point.array_sub(weighted_center(self, masses, indices));
+        self.translate(v);
+        v
+    }
+}
+
+/// sum(w_i * p_i) / sum(w_i) over the points selected by `indices`, with
+/// unit weights if `masses` is `None`.
+fn weighted_center<F, const N: usize>(points: &[[F; N]], masses: Option<&[F]>, indices: Option<&[usize]>) -> [F; N]
+where
+    F: Float + std::fmt::Debug,
+{
+    if let Some(m) = masses {
+        assert_eq!(m.len(), points.len(), "masses and points differ in size");
+    }
+    let mut center = [F::zero(); N];
+    let mut wsum = F::zero();
+    let mut add = |i: usize| {
+        let w = masses.map_or(F::one(), |m| m[i]);
+        center = center.array_add(points[i].array_scale(w));
+        wsum = wsum + w;
+    };
+    match indices {
+        Some(indices) => indices.iter().for_each(|&i| add(i)),
+        None => (0..points.len()).for_each(add),
+    }
+    center.array_scale(wsum.recip())
 }
 
 #[test]
-fn test_vecf3() {
-    use approx::*;
+fn test_vecf3_centers() {
+    let mut positions = [[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [0.0, 4.0, 6.0]];
+    let masses = [1.0, 3.0, 0.0];
+    assert_eq!(positions.centroid(), [2.0 / 3.0, 4.0 / 3.0, 2.0]);
+    assert_eq!(positions.centroid_of(&[0, 1]), [1.0, 0.0, 0.0]);
+    assert_eq!(positions.center_of_mass(&masses), [1.5, 0.0, 0.0]);
+    assert_eq!(positions.center_of_mass_of(&masses, &[0, 2]), [0.0; 3]);
+
+    positions.translate([1.0, 1.0, 1.0]);
+    assert_eq!(positions[0], [1.0; 3]);
+    positions.translate_subset(&[2], [0.0, -1.0, -1.0]);
+    assert_eq!(positions[2], [1.0, 4.0, 6.0]);
+
+    let v = positions.recenter([0.0; 3], Some(&masses), None);
+    assert_eq!(v, [-2.5, -1.0, -1.0]);
+    assert_eq!(positions.center_of_mass(&masses), [0.0; 3]);
+    positions.recenter([1.0, 2.0, 3.0], None, Some(&[1]));
+    assert_eq!(positions[1], [1.0, 2.0, 3.0]);
+
+    // other dimensions
+    let points = [[0.0, 1.0], [2.0, 3.0]];
+    assert_eq!(points.centroid(), [1.0, 2.0]);
+    assert!(<[[f64; 2]]>::centroid(&[])[0].is_nan());
+}
 
+#[test]
+fn test_vecf3() {
     #[cfg(feature = "nalgebra")]
     {
-        let a = vec![1.0, 2.0, 3.0];
+        use approx::*;
+
+        let a = [1.0, 2.0, 3.0];
         let x = a.to_vector();
         assert_relative_eq!(x.norm(), a.vec2norm(), epsilon = 1e-3);
     }
@@ -296,6 +742,8 @@ fn test_vecf3() {
 
     #[cfg(feature = "nalgebra")]
     {
+        use approx::*;
+
         let n = positions.as_flat().vec2norm();
         let m = positions.to_matrix();
         assert_relative_eq!(n, m.norm(), epsilon = 1e-4);
@@ -307,7 +755,7 @@ fn test_vecf3() {
     let flat = positions.as_flat();
     assert_eq!(18, flat.len());
 
-    let mut positions = positions.clone();
+    let mut positions = positions;
     let mflat = positions.as_mut_flat();
     mflat[0] = 0.0;
     assert_eq!(0.0, positions[0][0]);
@@ -316,8 +764,8 @@ fn test_vecf3() {
 
 // [[file:../vecfx.note::*for Vec<\[f64; 3\]>][for Vec<[f64; 3]>:2]]
 #[cfg(feature = "nalgebra")]
-impl VecFloatAs3D for Vector3fVec {
-    fn as_3d(&self) -> &[[f64; 3]] {
+impl<F: Float + na::Scalar> VecFloatAs3D<F> for na::Matrix3xX<F> {
+    fn as_3d(&self) -> &[[F; 3]] {
         assert_eq!(
             0,
             self.len() % 3,
@@ -328,7 +776,7 @@ impl VecFloatAs3D for Vector3fVec {
         self.as_slice().as_3d()
     }
 
-    fn as_mut_3d(&mut self) -> &mut [[f64; 3]] {
+    fn as_mut_3d(&mut self) -> &mut [[F; 3]] {
         assert_eq!(
             0,
             self.len() % 3,
@@ -346,48 +794,76 @@ fn test_as_3d() {
     let p = v.as_3d();
     assert_eq!(&[[1., 2., 3.]], p);
 
-    let mut v = vec![1., 2., 3., 4., 5., 6.];
+    let mut v = [1., 2., 3., 4., 5., 6.];
     let p = &mut v.as_mut_3d();
     assert_eq!(p, &mut [[1., 2., 3.], [4., 5., 6.],]);
 }
 
+#[test]
+fn test_as_nd() {
+    // 2D projections
+    let mut v = [1.0f32, 2., 3., 4.];
+    assert_eq!(v.as_nd::<2>(), &[[1., 2.], [3., 4.]]);
+    v.as_mut_nd::<2>()[1][0] = 5.;
+    assert_eq!(v, [1., 2., 5., 4.]);
+
+    // 6-component stress tensors
+    let mut stress = [[0.0; 6]; 2];
+    stress.as_mut_flat()[7] = 1.0;
+    assert_eq!(stress[1][1], 1.0);
+    assert_eq!(stress.as_flat().as_nd::<6>(), &stress);
+    assert_eq!(stress.ravel().len(), 12);
+}
+
+#[test]
+#[should_panic]
+fn test_as_nd_mismatch() {
+    let _ = [1.0, 2.0, 3.0].as_nd::<4>();
+}
+
 #[test]
 #[cfg(feature = "nalgebra")]
 fn test_as_3d_na() {
     let p = [1., 2., 3.];
-    let mut m = p.as_3d().to_matrix();
+    let m = p.as_3d().to_matrix();
     let _ = m.norm();
 
-    let mut v = vec![[1., 2., 3.], [4., 5., 6.]].to_matrix();
+    let mut v = [[1., 2., 3.], [4., 5., 6.]].to_matrix();
     let mp = v.as_mut_3d();
     assert_eq!(mp, &mut [[1., 2., 3.], [4., 5., 6.],]);
 
     mp[0][0] = 1.1;
     assert_eq!(1.1, v[(0, 0)]);
+
+    // 4D homogeneous coordinates
+    let m = [[1., 2., 3., 1.], [4., 5., 6., 1.]].to_matrix();
+    assert_eq!(m.shape(), (4, 2));
+    assert_eq!(m[(3, 1)], 1.0);
 }
 // for Vec<[f64; 3]>:2 ends here
 
 // [[file:../vecfx.note::4edfda94][4edfda94]]
 #[cfg(feature = "nalgebra")]
 mod slice {
+    use nalgebra as na;
     use nalgebra::DVectorSlice;
     use nalgebra::DVectorSliceMut;
 
-    pub trait SliceNaExt {
+    pub trait SliceNaExt<F: na::Scalar = f64> {
         /// View of mut flat slice
-        fn as_vector_slice(&self) -> DVectorSlice<f64>;
-        fn as_vector_slice_mut(&mut self) -> DVectorSliceMut<f64>;
+        fn as_vector_slice(&self) -> DVectorSlice<'_, F>;
+        fn as_vector_slice_mut(&mut self) -> DVectorSliceMut<'_, F>;
     }
 
-    impl SliceNaExt for [f64] {
+    impl<F: na::Scalar> SliceNaExt<F> for [F] {
         /// A column vector with slice storage.
-        fn as_vector_slice(&self) -> DVectorSlice<f64> {
+        fn as_vector_slice(&self) -> DVectorSlice<'_, F> {
             // NOTE: DVectorSlice::from does not work (nalgebra v0.29)
             DVectorSlice::from_slice(self, self.len())
         }
 
         /// A column vector with mutable slice storage.
-        fn as_vector_slice_mut(&mut self) -> DVectorSliceMut<f64> {
+        fn as_vector_slice_mut(&mut self) -> DVectorSliceMut<'_, F> {
             DVectorSliceMut::from_slice(self, self.len())
         }
     }
@@ -418,6 +894,7 @@ fn test_vector3f() {
     assert_relative_eq!(c.sum(), 12.0);
 }
 
+#[cfg(feature = "nalgebra")]
 #[test]
 fn test_na_slice() {
     let x = [1.0, 2.0];